@@ -0,0 +1,90 @@
+//! Inputs shared by the benchmarks and the symbol table implementation
+//! `encode_uri_component` and `decode_uri_component` used before the
+//! single-pass rewrite, kept as a baseline to compare against.
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+use url_search_params::SYMBOL;
+
+pub fn inputs() -> Vec<(&'static str, String)> {
+    vec![
+        ("short_alphanumeric", "key123value".to_string()),
+        ("special_characters", "\r\n \"%!#$&'()*+,/:;=?@[]".repeat(4)),
+        ("large_utf8", "Добрий вечір, we are from Ukraine! ".repeat(256)),
+    ]
+}
+
+pub fn symbol_table_encode_uri_component(component: &str) -> String {
+    let mut _result = component.replace(SYMBOL.percent, "%25");
+    _result = _result.replace(SYMBOL.whitespace, "%20");
+    _result = _result.replace(SYMBOL.carriage_return, "%0D");
+    _result = _result.replace(SYMBOL.new_line, "%0A");
+    _result = _result.replace(SYMBOL.exclamation_mark, "%21");
+    _result = _result.replace(SYMBOL.quotation_mark, "%22");
+    _result = _result.replace(SYMBOL.number_sign, "%23");
+    _result = _result.replace(SYMBOL.dollar, "%24");
+    _result = _result.replace(SYMBOL.ampersand, "%26");
+    _result = _result.replace(SYMBOL.single_quote, "%27");
+    _result = _result.replace(SYMBOL.opening_bracket, "%28");
+    _result = _result.replace(SYMBOL.closing_bracket, "%29");
+    _result = _result.replace(SYMBOL.asterisk, "%2A");
+    _result = _result.replace(SYMBOL.plus, "%2B");
+    _result = _result.replace(SYMBOL.comma, "%2C");
+    _result = _result.replace(SYMBOL.slash, "%2F");
+    _result = _result.replace(SYMBOL.colon, "%3A");
+    _result = _result.replace(SYMBOL.semicolon, "%3B");
+    _result = _result.replace(SYMBOL.equals, "%3D");
+    _result = _result.replace(SYMBOL.at, "%40");
+    _result = _result.replace(SYMBOL.opening_square_bracket, "%5B");
+    _result = _result.replace(SYMBOL.closing_square_bracket, "%5D");
+
+    _result
+}
+
+pub fn symbol_table_decode_uri_component(component: &str) -> String {
+    let mut _result = component.replace( "%20", SYMBOL.whitespace);
+    _result = _result.replace("%0A", SYMBOL.new_line);
+    _result = _result.replace ("%0D", SYMBOL.carriage_return);
+    _result = _result.replace ("%21", SYMBOL.exclamation_mark);
+    _result = _result.replace ("%22", SYMBOL.quotation_mark);
+    _result = _result.replace ("%23", SYMBOL.number_sign);
+    _result = _result.replace ("%24", SYMBOL.dollar);
+    _result = _result.replace ("%25", SYMBOL.percent);
+    _result = _result.replace ("%26", SYMBOL.ampersand);
+    _result = _result.replace ("%27", SYMBOL.single_quote);
+    _result = _result.replace ("%28", SYMBOL.opening_bracket);
+    _result = _result.replace ("%29", SYMBOL.closing_bracket);
+    _result = _result.replace ("%2A", SYMBOL.asterisk);
+    _result = _result.replace ("%2B", SYMBOL.plus);
+    _result = _result.replace ("%2C", SYMBOL.comma);
+    _result = _result.replace ("%2F", SYMBOL.slash);
+    _result = _result.replace ("%3A", SYMBOL.colon);
+    _result = _result.replace ("%3B", SYMBOL.semicolon);
+    _result = _result.replace ("%3D", SYMBOL.equals);
+    _result = _result.replace ("%3F", SYMBOL.question_mark);
+    _result = _result.replace ("%40", SYMBOL.at);
+    _result = _result.replace ("%5B", SYMBOL.opening_square_bracket);
+    _result = _result.replace ("%5D", SYMBOL.closing_square_bracket);
+
+    _result
+}
+
+/// Time both implementations on the same input and print how many times
+/// the single-pass one is faster, a quick summary on top of criterion report
+pub fn report_speedup(name: &str, input: &str, symbol_table: fn(&str) -> String, single_pass: fn(&str) -> String) {
+    let symbol_table_time = measure(input, symbol_table);
+    let single_pass_time = measure(input, single_pass);
+
+    let speedup = symbol_table_time.as_secs_f64() / single_pass_time.as_secs_f64();
+    let verdict = if speedup > 1.0 { "faster" } else { "SLOWER" };
+    println!("{}: single-pass is {:.2}x {} than symbol table", name, speedup, verdict);
+}
+
+fn measure(input: &str, function: fn(&str) -> String) -> Duration {
+    let iterations = 2_000;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(function(std::hint::black_box(input)));
+    }
+    start.elapsed()
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use url_search_params::{decode_uri_component, encode_uri_component};
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_uri_component");
+
+    let inputs : Vec<(&str, String)> = common::inputs()
+        .into_iter()
+        .map(|(name, input)| (name, encode_uri_component(&input)))
+        .collect();
+
+    for (name, input) in &inputs {
+        group.bench_with_input(BenchmarkId::new("symbol_table", name), input, |b, input| {
+            b.iter(|| common::symbol_table_decode_uri_component(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("single_pass", name), input, |b, input| {
+            b.iter(|| decode_uri_component(black_box(input)))
+        });
+    }
+    group.finish();
+
+    for (name, input) in &inputs {
+        common::report_speedup(&format!("decode {}", name), input, common::symbol_table_decode_uri_component, decode_uri_component);
+    }
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use url_search_params::encode_uri_component;
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_uri_component");
+
+    for (name, input) in common::inputs() {
+        group.bench_with_input(BenchmarkId::new("symbol_table", name), &input, |b, input| {
+            b.iter(|| common::symbol_table_encode_uri_component(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("single_pass", name), &input, |b, input| {
+            b.iter(|| encode_uri_component(black_box(input)))
+        });
+    }
+    group.finish();
+
+    for (name, input) in common::inputs() {
+        common::report_speedup(&format!("encode {}", name), &input, common::symbol_table_encode_uri_component, encode_uri_component);
+    }
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
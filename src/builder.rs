@@ -0,0 +1,213 @@
+use std::fmt;
+use crate::encode_uri_component_into;
+
+/// Builder of a query string, keeping parameters in the order they are
+/// appended
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::QueryStringBuilder;
+///
+/// let query = QueryStringBuilder::new()
+///     .append("q", "rust url")
+///     .append("page", "2")
+///     .build();
+///
+/// assert_eq!(query, "q=rust%20url&page=2");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryStringBuilder {
+    query: String,
+}
+
+impl QueryStringBuilder {
+    /// Create builder of an empty query string
+    pub fn new() -> QueryStringBuilder {
+        QueryStringBuilder { query: String::new() }
+    }
+
+    /// Append parameter, encoding both key and value
+    pub fn append<K: AsRef<str>, V: AsRef<str>>(mut self, key: K, value: V) -> Self {
+        self.push_key(key.as_ref());
+        encode_uri_component_into(value.as_ref(), &mut self.query);
+        self
+    }
+
+    /// Append parameter with already percent-encoded value
+    ///
+    /// Key is encoded, value is appended as is, so it is not corrupted by
+    /// encoding it again. Caller is responsible for the value being
+    /// properly encoded, a raw `&` or `#` in it breaks the query string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::QueryStringBuilder;
+    ///
+    /// let query = QueryStringBuilder::new()
+    ///     .append("file", "a b.txt")
+    ///     .append_raw("signature", "abc%2Bdef%3D")
+    ///     .build();
+    ///
+    /// assert_eq!(query, "file=a%20b.txt&signature=abc%2Bdef%3D");
+    /// ```
+    pub fn append_raw<K: AsRef<str>, V: AsRef<str>>(mut self, key: K, value: V) -> Self {
+        self.push_key(key.as_ref());
+        self.query.push_str(value.as_ref());
+        self
+    }
+
+    /// Append flag parameter, a key without value and without trailing `=`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::QueryStringBuilder;
+    ///
+    /// let query = QueryStringBuilder::new()
+    ///     .append_flag("debug")
+    ///     .append("verbose", "1")
+    ///     .build();
+    ///
+    /// assert_eq!(query, "debug&verbose=1");
+    /// ```
+    pub fn append_flag<K: AsRef<str>>(mut self, key: K) -> Self {
+        if !self.query.is_empty() {
+            self.query.push('&');
+        }
+        encode_uri_component_into(key.as_ref(), &mut self.query);
+        self
+    }
+
+    /// Append parameter only if the value is present, formatting it via
+    /// `Display`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::QueryStringBuilder;
+    ///
+    /// let limit: Option<u32> = Some(10);
+    /// let cursor: Option<&str> = None;
+    ///
+    /// let query = QueryStringBuilder::new()
+    ///     .append_if_some("limit", limit)
+    ///     .append_if_some("cursor", cursor)
+    ///     .build();
+    ///
+    /// assert_eq!(query, "limit=10");
+    /// ```
+    pub fn append_if_some<K: AsRef<str>, T: fmt::Display>(self, key: K, value: Option<T>) -> Self {
+        match value {
+            Some(value) => self.append(key, value.to_string()),
+            None => self,
+        }
+    }
+
+    /// Append parameter only if the condition holds
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::QueryStringBuilder;
+    ///
+    /// let query = QueryStringBuilder::new()
+    ///     .append_if(true, "verbose", "1")
+    ///     .append_if(false, "debug", "1")
+    ///     .build();
+    ///
+    /// assert_eq!(query, "verbose=1");
+    /// ```
+    pub fn append_if<K: AsRef<str>, V: AsRef<str>>(self, condition: bool, key: K, value: V) -> Self {
+        if condition {
+            self.append(key, value)
+        } else {
+            self
+        }
+    }
+
+    /// Get the built query string
+    pub fn build(self) -> String {
+        self.query
+    }
+
+    fn push_key(&mut self, key: &str) {
+        if !self.query.is_empty() {
+            self.query.push('&');
+        }
+        encode_uri_component_into(key, &mut self.query);
+        self.query.push('=');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{flag_set, parse_url_search_params_ordered, QueryStringBuilder};
+
+    #[test]
+    fn build() {
+        let query = QueryStringBuilder::new()
+            .append("z", "last letter")
+            .append("a&b", "c=d")
+            .append("z", String::from("again"))
+            .append("empty", "")
+            .build();
+
+        assert_eq!("z=last%20letter&a%26b=c%3Dd&z=again&empty=", query);
+
+        let pairs = parse_url_search_params_ordered(&query);
+        assert_eq!(4, pairs.len());
+        assert_eq!(("a&b".to_string(), "c=d".to_string()), pairs[1]);
+    }
+
+    #[test]
+    fn append_raw() {
+        let query = QueryStringBuilder::new()
+            .append_raw("signed blob", "x%2Fy%3D%3D")
+            .append("plain", "x/y==")
+            .build();
+
+        assert_eq!("signed%20blob=x%2Fy%3D%3D&plain=x%2Fy%3D%3D", query);
+
+        let params = parse_url_search_params_ordered(&query);
+        assert_eq!(params[0].1, params[1].1);
+    }
+
+    #[test]
+    fn append_optional() {
+        let since: Option<u64> = None;
+        let verbose = true;
+        let query = QueryStringBuilder::new()
+            .append_if_some("limit", Some(20))
+            .append_if_some("since", since)
+            .append_if_some("q", Some("a b"))
+            .append_if(!verbose, "quiet", "1")
+            .append_if(verbose, "verbose", "1")
+            .build();
+
+        assert_eq!("limit=20&q=a%20b&verbose=1", query);
+    }
+
+    #[test]
+    fn append_flag() {
+        let query = QueryStringBuilder::new()
+            .append_flag("dry run")
+            .append("a", "")
+            .append_flag("debug")
+            .build();
+
+        assert_eq!("dry%20run&a=&debug", query);
+
+        let flags = flag_set(&query);
+        assert_eq!(2, flags.len());
+        assert!(flags.contains("dry run"));
+        assert!(!flags.contains("a"));
+    }
+
+    #[test]
+    fn build_empty() {
+        assert_eq!("", QueryStringBuilder::new().build());
+        assert_eq!(QueryStringBuilder::new(), QueryStringBuilder::default());
+    }
+}
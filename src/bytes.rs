@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use crate::percent_decode_into;
+
+/// Convert given bytes into a HashMap containing query string parameters as
+/// key-value pairs
+///
+/// Works as [parse_url_search_params](crate::parse_url_search_params), but
+/// input does not have to be valid UTF-8: it is split on `&` and `=` bytes
+/// and percent-decoded byte by byte, only decoded keys and values are
+/// converted to UTF-8, replacing invalid sequences with U+FFFD replacement
+/// character.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_bytes;
+///
+/// let params = parse_bytes(b"key=caf%C3%A9&another_key=its_value");
+/// assert_eq!(params.get("key"), Some(&"café".to_string()));
+/// assert_eq!(params.get("another_key"), Some(&"its_value".to_string()));
+/// ```
+pub fn parse_bytes(params: &[u8]) -> HashMap<String, String> {
+    parse_bytes_ordered(params).into_iter().collect()
+}
+
+/// Convert given bytes into key-value pairs, in the order they appear, see [parse_bytes]
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_bytes_ordered;
+///
+/// let pairs = parse_bytes_ordered(b"b=1&a=2&b=3");
+/// assert_eq!(pairs[0], ("b".to_string(), "1".to_string()));
+/// assert_eq!(pairs[2], ("b".to_string(), "3".to_string()));
+/// ```
+pub fn parse_bytes_ordered(params: &[u8]) -> Vec<(String, String)> {
+    parse_bytes_raw(params)
+        .into_iter()
+        .map(|(key, value)| (into_string_lossy(key), into_string_lossy(value)))
+        .collect()
+}
+
+/// Convert given bytes into decoded key-value pairs, in the order they
+/// appear, without any UTF-8 validation
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_bytes_raw;
+///
+/// let pairs = parse_bytes_raw(b"token=%00%FF&k\xFF=v");
+/// assert_eq!(pairs[0], (b"token".to_vec(), vec![0x00, 0xFF]));
+/// assert_eq!(pairs[1], (b"k\xFF".to_vec(), b"v".to_vec()));
+/// ```
+pub fn parse_bytes_raw(params: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    if std::str::from_utf8(params).is_ok_and(|params| params.trim().is_empty()) {
+        return vec![]
+    }
+
+    params.split(|byte| *byte == b'&')
+        .filter_map(|param| {
+            let mut key_value = param.splitn(2, |byte| *byte == b'=');
+            let key = key_value.next().unwrap_or_default();
+            let value = key_value.next().unwrap_or_default();
+
+            if key.is_empty() {
+                return None
+            }
+
+            let mut decoded_key : Vec<u8> = Vec::with_capacity(key.len());
+            percent_decode_into(key, &mut decoded_key);
+
+            let mut decoded_value : Vec<u8> = Vec::with_capacity(value.len());
+            percent_decode_into(value, &mut decoded_value);
+
+            Some((decoded_key, decoded_value))
+        })
+        .collect()
+}
+
+/// Decode percent-encoded string into raw bytes, without assuming UTF-8
+///
+/// Decoding rules are the same as for [decode_uri_component](crate::decode_uri_component),
+/// malformed escapes are kept as is, but decoded bytes are returned as they
+/// are, so binary values can be represented.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::decode_uri_component_bytes;
+///
+/// assert_eq!(decode_uri_component_bytes("%00%FFa%20"), vec![0x00, 0xFF, b'a', b' ']);
+/// assert_eq!(decode_uri_component_bytes("100%"), b"100%".to_vec());
+/// ```
+pub fn decode_uri_component_bytes(component: &str) -> Vec<u8> {
+    let mut decoded : Vec<u8> = Vec::with_capacity(component.len());
+    percent_decode_into(component.as_bytes(), &mut decoded);
+    decoded
+}
+
+fn into_string_lossy(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(decoded) => decoded,
+        Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode_uri_component, decode_uri_component_bytes, encode_uri_component, parse_bytes, parse_bytes_ordered, parse_bytes_raw, parse_url_search_params};
+
+    #[test]
+    fn parse_bytes_matches_str_parse() {
+        let search_params = "a=1&b=hello%20world&=skipped&c=d=e&a=2";
+        assert_eq!(parse_url_search_params(search_params), parse_bytes(search_params.as_bytes()));
+        assert!(parse_bytes(b"").is_empty());
+        assert!(parse_bytes(b"  ").is_empty());
+
+        for search_params in ["\x0B", "\x0B\t", "\u{A0}"] {
+            assert_eq!(parse_url_search_params(search_params), parse_bytes(search_params.as_bytes()));
+        }
+        assert!(parse_bytes(b"\x0B").is_empty());
+        assert_eq!(1, parse_bytes(b" \xFF").len());
+    }
+
+    #[test]
+    fn parse_bytes_non_utf8_input() {
+        let pairs = parse_bytes_ordered(b"a=\xFF%41&b=%C3%A9");
+        assert_eq!(vec![
+            ("a".to_string(), "\u{FFFD}A".to_string()),
+            ("b".to_string(), "é".to_string()),
+        ], pairs);
+    }
+
+    #[test]
+    fn parse_bytes_raw_binary_values() {
+        let pairs = parse_bytes_raw(b"blob=%00%01%FE%FF&text=%26");
+        assert_eq!(vec![
+            (b"blob".to_vec(), vec![0x00, 0x01, 0xFE, 0xFF]),
+            (b"text".to_vec(), b"&".to_vec()),
+        ], pairs);
+    }
+
+    #[test]
+    fn decode_uri_component_bytes_test() {
+        let all_bytes: String = (0u8..=255).map(|byte| format!("%{:02X}", byte)).collect();
+        assert_eq!((0u8..=255).collect::<Vec<u8>>(), decode_uri_component_bytes(&all_bytes));
+
+        assert_eq!(b"a%ZZ%2".to_vec(), decode_uri_component_bytes("a%ZZ%2"));
+        assert_eq!("ключ".as_bytes().to_vec(), decode_uri_component_bytes(&encode_uri_component("ключ")));
+        assert_eq!(decode_uri_component("%C3%BC").into_bytes(), decode_uri_component_bytes("%c3%bc"));
+        assert!(decode_uri_component_bytes("").is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hint::black_box;
+use crate::{build_url_search_params_ordered, decode_uri_component, raw_optional_pairs, raw_pairs};
+
+/// Options of [canonicalize_with]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalizeOptions {
+    /// Lowercase decoded keys, off by default. Keep in mind it is lossy for
+    /// systems where key case matters, `A=1` and `a=1` become the same parameter.
+    pub lowercase_keys: bool,
+    /// Sort parameters by key and then by value, on by default
+    pub sort: bool,
+}
+
+impl Default for CanonicalizeOptions {
+    fn default() -> Self {
+        CanonicalizeOptions {
+            lowercase_keys: false,
+            sort: true,
+        }
+    }
+}
+
+/// Convert given query string into the canonical form, using default
+/// [CanonicalizeOptions]
+///
+/// Parameters are decoded, sorted by key and then by value, and encoded
+/// back, so semantically equal query strings get the same canonical form.
+/// Duplicate keys are kept, parameters with empty keys are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::canonicalize;
+///
+/// assert_eq!(canonicalize("b=2&a=%7E&a=1"), canonicalize("a=1&a=~&b=%32"));
+/// assert_eq!(canonicalize("b=2&a=%7E&a=1"), "a=1&a=~&b=2");
+/// ```
+pub fn canonicalize(query: &str) -> String {
+    canonicalize_with(query, &CanonicalizeOptions::default())
+}
+
+/// Convert given query string into the canonical form according to the options
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{canonicalize_with, CanonicalizeOptions};
+///
+/// let options = CanonicalizeOptions { lowercase_keys: true, sort: true };
+/// assert_eq!(canonicalize_with("B=2&A=1", &options), "a=1&b=2");
+/// ```
+pub fn canonicalize_with(query: &str, options: &CanonicalizeOptions) -> String {
+    let mut pairs : Vec<(String, String)> = raw_pairs(query)
+        .map(|(key, value)| {
+            let mut key = decode_uri_component(key);
+            if options.lowercase_keys {
+                key = key.to_lowercase();
+            }
+            (key, decode_uri_component(value))
+        })
+        .collect();
+
+    if options.sort {
+        pairs.sort();
+    }
+
+    build_url_search_params_ordered(&pairs)
+}
+
+/// Sort parameters of the given query string by key and then by value
+///
+/// Keys and values are compared decoded, bytewise, nothing is lowercased.
+/// Parameters are encoded back, so escapes are normalized: `%7E` becomes
+/// `~`, lowercase hex digits become uppercase and so on. Duplicate keys are
+/// kept, parameters with empty keys are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::sort_query;
+///
+/// assert_eq!(sort_query("b=2&B=1&a=%7e"), "B=1&a=~&b=2");
+/// ```
+pub fn sort_query(query: &str) -> String {
+    let options = CanonicalizeOptions {
+        lowercase_keys: false,
+        sort: true,
+    };
+    canonicalize_with(query, &options)
+}
+
+/// Compare two query strings for semantic equality in constant time
+///
+/// Both query strings are brought into the canonical form with
+/// [canonicalize] first, then every byte of the longer canonical form is
+/// inspected regardless of where the first difference is, so the time
+/// spent does not reveal how long the matching prefix is.
+///
+/// Intended for verifying signed URLs. Only the final comparison is
+/// constant time: canonicalization itself takes time depending on the
+/// input, and the length of the canonical forms is not hidden. The inputs
+/// and the running difference go through [std::hint::black_box], so the
+/// compiler can not turn the loop into an early exit. That is a best
+/// effort hint, prefer comparing HMACs of canonical queries with a
+/// dedicated constant time crate where possible.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::secure_query_eq;
+///
+/// assert!(secure_query_eq("b=2&a=%7E", "a=~&b=%32"));
+/// assert!(!secure_query_eq("a=1", "a=2"));
+/// ```
+pub fn secure_query_eq(a: &str, b: &str) -> bool {
+    let a = canonicalize(a);
+    let b = canonicalize(b);
+    let (a, b) = (black_box(a.as_bytes()), black_box(b.as_bytes()));
+
+    let mut difference = a.len() ^ b.len();
+    for index in 0..a.len().max(b.len()) {
+        let left = a.get(index).copied().unwrap_or(0);
+        let right = b.get(index).copied().unwrap_or(0);
+        difference = black_box(difference | usize::from(left ^ right));
+    }
+
+    black_box(difference) == 0
+}
+
+/// Reorder parameters of the query string to follow key order of the template
+///
+/// Keys are compared decoded, parameters themselves are kept exactly as
+/// they are in the query string, without decoding and encoding them again.
+/// Parameters with the same key keep their relative order, parameters with
+/// keys missing from the template are appended at the end in their
+/// original order. Parameters with empty keys are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::reorder_like;
+///
+/// assert_eq!(reorder_like("c=3&extra=x&a=1&b=%20", "a=&b=&c="), "a=1&b=%20&c=3&extra=x");
+/// ```
+pub fn reorder_like(query: &str, template: &str) -> String {
+    let mut positions : HashMap<String, usize> = HashMap::new();
+    for (key, _) in raw_pairs(template) {
+        let position = positions.len();
+        positions.entry(decode_uri_component(key)).or_insert(position);
+    }
+
+    let mut params : Vec<(usize, String)> = raw_optional_pairs(query)
+        .map(|(key, value)| {
+            let position = positions.get(&decode_uri_component(key)).copied().unwrap_or(usize::MAX);
+            let param = match value {
+                Some(value) => [key, "=", value].join(""),
+                None => key.to_string(),
+            };
+            (position, param)
+        })
+        .collect();
+    params.sort_by_key(|(position, _)| *position);
+
+    params.into_iter()
+        .map(|(_, param)| param)
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Query string in the canonical form, see [canonicalize]
+///
+/// Semantically equal query strings hash and compare equal, so it can be
+/// used as a key of a map, for example a cache.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::CanonicalQuery;
+///
+/// assert_eq!(CanonicalQuery::new("a=1&b=2"), CanonicalQuery::new("b=2&a=%31"));
+/// assert_eq!(CanonicalQuery::new("b=2&a=1").as_str(), "a=1&b=2");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalQuery(String);
+
+impl CanonicalQuery {
+    /// Canonicalize given query string
+    pub fn new(query: &str) -> CanonicalQuery {
+        CanonicalQuery(canonicalize(query))
+    }
+
+    /// Canonical form of the query string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CanonicalQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{canonicalize, canonicalize_with, reorder_like, secure_query_eq, sort_query, CanonicalQuery, CanonicalizeOptions};
+
+    #[test]
+    fn canonicalize_default() {
+        assert_eq!("A=1&a=2&b=%20", canonicalize("b=%20&a=2&A=1"));
+        assert_eq!("a=1&a=2", canonicalize("a=2&=x&a=1"));
+        assert_eq!("", canonicalize(""));
+    }
+
+    #[test]
+    fn canonicalize_lowercase_keys() {
+        let options = CanonicalizeOptions { lowercase_keys: true, sort: true };
+        assert_eq!("a=1&a=2&b=%C3%9C", canonicalize_with("b=%C3%9C&a=2&A=1", &options));
+        assert_eq!("key=value&key=value", canonicalize_with("KEY=value&Key=value", &options));
+    }
+
+    #[test]
+    fn canonicalize_without_sort() {
+        let options = CanonicalizeOptions { lowercase_keys: true, sort: false };
+        assert_eq!("b=2&a=1", canonicalize_with("B=%32&A=1", &options));
+    }
+
+    #[test]
+    fn canonical_query_equality() {
+        assert_eq!(CanonicalQuery::new("a=1&b=2"), CanonicalQuery::new("b=2&a=1"));
+        assert_eq!(CanonicalQuery::new("a=~&b=%20"), CanonicalQuery::new("b=%20&a=%7E"));
+        assert_ne!(CanonicalQuery::new("a=1&b=2"), CanonicalQuery::new("a=1&b=3"));
+        assert!(CanonicalQuery::new("a=1") < CanonicalQuery::new("b=1"));
+    }
+
+    #[test]
+    fn canonical_query_as_map_key() {
+        let mut cache: HashMap<CanonicalQuery, u32> = HashMap::new();
+        cache.insert(CanonicalQuery::new("page=2&q=rust"), 1);
+        cache.insert(CanonicalQuery::new("q=rust&page=2"), 2);
+
+        assert_eq!(1, cache.len());
+        assert_eq!(Some(&2), cache.get(&CanonicalQuery::new("q=%72ust&page=2")));
+        assert_eq!("page=2&q=rust", CanonicalQuery::new("q=rust&page=2").to_string());
+    }
+
+    #[test]
+    fn sort_query_test() {
+        assert_eq!("a=1&a=2&b=x%20y", sort_query("b=x%20y&a=2&a=1"));
+        assert_eq!("A=1&a=1", sort_query("a=1&A=1"));
+        assert_eq!("a=%2B&c=%3D", sort_query("c=%3d&a=%2b"));
+        assert_eq!("", sort_query(""));
+    }
+
+    #[test]
+    fn reorder_like_test() {
+        let template = "timestamp=&nonce=&amount=&currency=";
+        let query = "currency=UAH&amount=100%2E00&extra=1&nonce=abc&timestamp=1700000000";
+
+        assert_eq!("timestamp=1700000000&nonce=abc&amount=100%2E00&currency=UAH&extra=1", reorder_like(query, template));
+    }
+
+    #[test]
+    fn reorder_like_duplicates_and_encoded_keys() {
+        let template = "b&a%20key";
+        let query = "x=0&a+key=plus&a%20key=1&b=2&%61%20key=3&=dropped&b=4";
+
+        assert_eq!("b=2&b=4&a%20key=1&%61%20key=3&x=0&a+key=plus", reorder_like(query, template));
+        assert_eq!("", reorder_like("", template));
+        assert_eq!("b=1&a=2", reorder_like("b=1&a=2", ""));
+        assert_eq!("", reorder_like("   ", template));
+        assert_eq!("b&a=&x==1", reorder_like("x==1&a=&b", "b&a"));
+    }
+
+    #[test]
+    fn secure_query_eq_test() {
+        assert!(secure_query_eq("expires=1700000000&path=%2Ffile", "path=/file&expires=1700000000"));
+        assert!(secure_query_eq("", ""));
+        assert!(secure_query_eq("=ignored", ""));
+
+        assert!(!secure_query_eq("expires=1700000000&path=%2Ffile", "expires=1700000001&path=%2Ffile"));
+        assert!(!secure_query_eq("a=1&a=1", "a=1"));
+    }
+
+    #[test]
+    fn secure_query_eq_different_lengths() {
+        assert!(!secure_query_eq("signature=abc", "signature=abcdef"));
+        assert!(!secure_query_eq("signature=abcdef", "signature=abc"));
+        assert!(!secure_query_eq("", "signature=abc"));
+        assert!(!secure_query_eq("a=1", ""));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use encoding_rs::Encoding;
+use crate::{decode_uri_component, encode_byte_into, percent_decode_into, raw_pairs, ENCODE_TABLE, HEX_UPPER};
+
+/// Decode `application/x-www-form-urlencoded` component, interpreting
+/// decoded bytes in the given charset instead of UTF-8
+///
+/// `+` is decoded as whitespace, same as [PlusMode::Space](crate::PlusMode::Space).
+/// Malformed escapes are kept as is, bytes invalid in the charset are
+/// replaced with U+FFFD replacement character.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::decode_uri_component_charset;
+///
+/// let decoded = decode_uri_component_charset("%EA%EB%FE%F7+1", encoding_rs::WINDOWS_1251);
+/// assert_eq!(decoded, "ключ 1");
+/// ```
+pub fn decode_uri_component_charset(component: &str, encoding: &'static Encoding) -> String {
+    let mut decoded : Vec<u8> = Vec::with_capacity(component.len());
+    percent_decode_into(component.replace('+', " ").as_bytes(), &mut decoded);
+
+    encoding.decode_without_bom_handling(&decoded).0.into_owned()
+}
+
+/// Percent-encode given string in the given charset instead of UTF-8, the
+/// same way browsers submit forms of non-UTF-8 pages
+///
+/// Bytes are encoded as by [EncodeSet::FORM](crate::EncodeSet::FORM), so
+/// whitespace is encoded as `+` and `+` itself as `%2B`. Characters not
+/// representable in the charset are written as HTML numeric character
+/// references, such as `&#8364;`, and then encoded.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component_charset;
+///
+/// let encoded = encode_uri_component_charset("ключ 1", encoding_rs::WINDOWS_1251);
+/// assert_eq!(encoded, "%EA%EB%FE%F7+1");
+/// ```
+pub fn encode_uri_component_charset(component: &str, encoding: &'static Encoding) -> String {
+    let (bytes, _, _) = encoding.encode(component);
+
+    let mut encoded = String::with_capacity(bytes.len());
+    for byte in bytes.iter() {
+        if *byte == b' ' {
+            encoded.push('+');
+        } else {
+            encode_byte_into(*byte, &ENCODE_TABLE, HEX_UPPER, &mut encoded);
+        }
+    }
+    encoded
+}
+
+/// Convert given string into a HashMap containing query string parameters,
+/// decoding keys and values in the given charset
+///
+/// Keys and values are decoded with [decode_uri_component_charset], so `+`
+/// is decoded as whitespace, as in form submissions `_charset_` comes from.
+/// Works as [parse_url_search_params](crate::parse_url_search_params) in
+/// every other way. Use [sniff_charset] to pick the charset sent along in
+/// `_charset_` parameter.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_url_search_params_charset, sniff_charset};
+///
+/// let query = "_charset_=windows-1251&q=%EA%EB%FE%F7";
+/// let encoding = sniff_charset(query).unwrap_or(encoding_rs::UTF_8);
+///
+/// let params = parse_url_search_params_charset(query, encoding);
+/// assert_eq!(params.get("q"), Some(&"ключ".to_string()));
+/// ```
+pub fn parse_url_search_params_charset(params: &str, encoding: &'static Encoding) -> HashMap<String, String> {
+    let mut params_map : HashMap<String, String> = HashMap::new();
+
+    for (key, value) in raw_pairs(params) {
+        params_map.insert(decode_uri_component_charset(key, encoding), decode_uri_component_charset(value, encoding));
+    }
+    params_map
+}
+
+/// Get charset named by the `_charset_` parameter, which browsers fill in
+/// with the charset of the submitted form
+///
+/// Returns `None` if there is no such parameter or the label is unknown.
+/// If the parameter is repeated, the first one is used.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::sniff_charset;
+///
+/// assert_eq!(sniff_charset("a=1&_charset_=Shift_JIS"), Some(encoding_rs::SHIFT_JIS));
+/// assert_eq!(sniff_charset("a=1"), None);
+/// ```
+pub fn sniff_charset(params: &str) -> Option<&'static Encoding> {
+    raw_pairs(params)
+        .find(|(key, _)| decode_uri_component(key) == "_charset_")
+        .and_then(|(_, value)| Encoding::for_label(decode_uri_component(value).trim().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{SHIFT_JIS, UTF_8, WINDOWS_1251, WINDOWS_1252};
+    use crate::{decode_uri_component_charset, encode_uri_component, encode_uri_component_charset, encode_uri_component_with, parse_form, parse_url_search_params_charset, sniff_charset, EncodeSet};
+
+    #[test]
+    fn round_trip() {
+        let cases = [
+            ("ключ значення", WINDOWS_1251),
+            ("café", WINDOWS_1252),
+            ("日本語", SHIFT_JIS),
+        ];
+
+        for (text, encoding) in cases {
+            let encoded = encode_uri_component_charset(text, encoding);
+            assert_ne!(encode_uri_component(text), encoded);
+            assert_eq!(text, decode_uri_component_charset(&encoded, encoding));
+        }
+
+        assert_eq!("caf%E9", encode_uri_component_charset("café", WINDOWS_1252));
+        assert_eq!("%93%FA%96%7B", encode_uri_component_charset("日本", SHIFT_JIS));
+    }
+
+    #[test]
+    fn utf8_matches_default() {
+        let text = "a b+c&d=ключ";
+        assert_eq!(encode_uri_component_with(text, &EncodeSet::FORM), encode_uri_component_charset(text, UTF_8));
+
+        let params = parse_form("q=%D0%BA+%ZZ%2B");
+        assert_eq!(Some(&decode_uri_component_charset("%D0%BA+%ZZ%2B", UTF_8)), params.get("q"));
+    }
+
+    #[test]
+    fn unmappable_characters() {
+        let encoded = encode_uri_component_charset("€ й", WINDOWS_1251);
+        assert_eq!("%88+%E9", encoded);
+
+        let encoded = encode_uri_component_charset("й", WINDOWS_1252);
+        assert_eq!("%26%231081%3B", encoded);
+        assert_eq!("&#1081;", decode_uri_component_charset(&encoded, WINDOWS_1252));
+    }
+
+    #[test]
+    fn parse_with_sniffed_charset() {
+        let query = "_charset_=windows-1251&q=%EA%EB%FE%F7&%EA=1";
+
+        let encoding = sniff_charset(query);
+        assert_eq!(Some(WINDOWS_1251), encoding);
+
+        let params = parse_url_search_params_charset(query, WINDOWS_1251);
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("q");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "ключ");
+
+        assert_eq!(Some(&"1".to_string()), params.get("к"));
+
+        assert_eq!(None, sniff_charset("_charset_=no-such-charset"));
+        assert_eq!(Some(SHIFT_JIS), sniff_charset("%5Fcharset%5F=%20shift_jis%20&_charset_=utf-8"));
+    }
+
+    #[test]
+    fn plus_as_whitespace() {
+        let params = parse_url_search_params_charset("q=%EA+%EB&%EA+%2B=1", WINDOWS_1251);
+
+        let boxed_get = params.get("q");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "к л");
+
+        assert_eq!(Some(&"1".to_string()), params.get("к +"));
+
+        let encoded = encode_uri_component_charset("к л+", WINDOWS_1251);
+        assert_eq!("%EA+%EB%2B", encoded);
+        assert_eq!("к л+", decode_uri_component_charset(&encoded, WINDOWS_1251));
+    }
+}
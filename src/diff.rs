@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use crate::parse_url_search_params;
+
+/// Difference between two query strings, as returned by [diff] and
+/// [UrlSearchParams::diff](crate::UrlSearchParams::diff)
+///
+/// All lists are sorted by key.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QueryDiff {
+    /// Parameters present only in the new query string
+    pub added: Vec<(String, String)>,
+    /// Parameters present only in the old query string
+    pub removed: Vec<(String, String)>,
+    /// Parameters present in both query strings with different values
+    pub changed: Vec<ChangedParam>,
+}
+
+/// Parameter with different values in the old and new query strings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedParam {
+    pub key: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+impl QueryDiff {
+    /// Check if both query strings contain the same parameters
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare decoded parameters of two query strings
+///
+/// Both query strings are parsed via [parse_url_search_params], so for
+/// duplicate keys the last value is compared.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{diff, ChangedParam};
+///
+/// let query_diff = diff("q=rust&page=1&sort=asc", "q=rust&page=2&lang=en");
+///
+/// assert_eq!(query_diff.added, vec![("lang".to_string(), "en".to_string())]);
+/// assert_eq!(query_diff.removed, vec![("sort".to_string(), "asc".to_string())]);
+/// assert_eq!(query_diff.changed, vec![ChangedParam {
+///     key: "page".to_string(),
+///     old_value: "1".to_string(),
+///     new_value: "2".to_string(),
+/// }]);
+/// ```
+pub fn diff(old: &str, new: &str) -> QueryDiff {
+    let old_params = parse_url_search_params(old);
+    let new_params = parse_url_search_params(new);
+
+    diff_maps(&old_params, &new_params)
+}
+
+pub(crate) fn diff_maps(old_params: &HashMap<String, String>, new_params: &HashMap<String, String>) -> QueryDiff {
+    let mut query_diff = QueryDiff::default();
+
+    for (key, old_value) in old_params {
+        match new_params.get(key) {
+            None => query_diff.removed.push((key.to_string(), old_value.to_string())),
+            Some(new_value) if new_value != old_value => query_diff.changed.push(ChangedParam {
+                key: key.to_string(),
+                old_value: old_value.to_string(),
+                new_value: new_value.to_string(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (key, new_value) in new_params {
+        if !old_params.contains_key(key) {
+            query_diff.added.push((key.to_string(), new_value.to_string()));
+        }
+    }
+
+    query_diff.added.sort();
+    query_diff.removed.sort();
+    query_diff.changed.sort_by(|a, b| a.key.cmp(&b.key));
+
+    query_diff
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{diff, ChangedParam};
+
+    #[test]
+    fn diff_realistic_query() {
+        let old = "q=rust%20lang&page=3&per_page=20&sort=relevance&utm_source=newsletter";
+        let new = "q=rust%20language&page=1&per_page=20&sort=relevance&lang=en&lang=uk";
+
+        let query_diff = diff(old, new);
+        assert!(!query_diff.is_empty());
+
+        assert_eq!(vec![("lang".to_string(), "uk".to_string())], query_diff.added);
+        assert_eq!(vec![("utm_source".to_string(), "newsletter".to_string())], query_diff.removed);
+        assert_eq!(vec![
+            ChangedParam {
+                key: "page".to_string(),
+                old_value: "3".to_string(),
+                new_value: "1".to_string(),
+            },
+            ChangedParam {
+                key: "q".to_string(),
+                old_value: "rust lang".to_string(),
+                new_value: "rust language".to_string(),
+            },
+        ], query_diff.changed);
+    }
+
+    #[test]
+    fn diff_same_params_different_order() {
+        let query_diff = diff("a=1&b=%20", "b=%20&a=1");
+        assert!(query_diff.is_empty());
+    }
+}
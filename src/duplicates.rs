@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use crate::{decode_uri_component, raw_pairs, UrlSearchParamsError};
+
+/// How [parse_with_duplicate_policy] resolves a key appearing more than once
+///
+/// Backends disagree on this: PHP keeps the last value, Go's `Values.Get`
+/// returns the first one and Python's `parse_qs` keeps all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep the first value, ignore the rest
+    FirstWins,
+    /// Keep the last value, same as [parse_url_search_params](crate::parse_url_search_params)
+    #[default]
+    LastWins,
+    /// Keep all values in the order they appear
+    Collect,
+    /// Fail with [UrlSearchParamsError::DuplicateKey]
+    Error,
+}
+
+/// Convert given string into a HashMap of keys with their values, resolving
+/// duplicate keys according to the policy
+///
+/// Every key has exactly one value unless the policy is
+/// [DuplicatePolicy::Collect]. Keys and values are decoded the same way
+/// [parse_url_search_params](crate::parse_url_search_params) does, pairs
+/// with empty keys are skipped. Only [DuplicatePolicy::Error] can fail.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_with_duplicate_policy, DuplicatePolicy, UrlSearchParamsError};
+///
+/// let params = parse_with_duplicate_policy("a=1&a=2", DuplicatePolicy::FirstWins).unwrap();
+/// assert_eq!(params.get("a"), Some(&vec!["1".to_string()]));
+///
+/// let params = parse_with_duplicate_policy("a=1&a=2", DuplicatePolicy::Collect).unwrap();
+/// assert_eq!(params.get("a"), Some(&vec!["1".to_string(), "2".to_string()]));
+///
+/// let boxed_params = parse_with_duplicate_policy("a=1&a=2", DuplicatePolicy::Error);
+/// assert_eq!(boxed_params, Err(UrlSearchParamsError::DuplicateKey("a".to_string())));
+/// ```
+pub fn parse_with_duplicate_policy(params: &str, policy: DuplicatePolicy) -> Result<HashMap<String, Vec<String>>, UrlSearchParamsError> {
+    let mut params_map : HashMap<String, Vec<String>> = HashMap::new();
+
+    for (key, value) in raw_pairs(params) {
+        let key = decode_uri_component(key);
+        let value = decode_uri_component(value);
+
+        match params_map.get_mut(&key) {
+            None => {
+                params_map.insert(key, vec![value]);
+            }
+            Some(values) => match policy {
+                DuplicatePolicy::FirstWins => {}
+                DuplicatePolicy::LastWins => values[0] = value,
+                DuplicatePolicy::Collect => values.push(value),
+                DuplicatePolicy::Error => return Err(UrlSearchParamsError::DuplicateKey(key)),
+            },
+        }
+    }
+    Ok(params_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_with_duplicate_policy, DuplicatePolicy, UrlSearchParamsError};
+
+    const QUERY: &str = "a=1&b=x&a=2&=skipped&a=%203";
+
+    #[test]
+    fn first_and_last_wins() {
+        let params = parse_with_duplicate_policy(QUERY, DuplicatePolicy::FirstWins).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+        assert_eq!(&vec!["1".to_string()], boxed_get.unwrap());
+
+        let params = parse_with_duplicate_policy(QUERY, DuplicatePolicy::LastWins).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+        assert_eq!(&vec![" 3".to_string()], boxed_get.unwrap());
+
+        assert_eq!(DuplicatePolicy::LastWins, DuplicatePolicy::default());
+    }
+
+    #[test]
+    fn collect() {
+        let params = parse_with_duplicate_policy(QUERY, DuplicatePolicy::Collect).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+        assert_eq!(&vec!["1".to_string(), "2".to_string(), " 3".to_string()], boxed_get.unwrap());
+
+        let boxed_get = params.get("b");
+        assert!(boxed_get.is_some());
+        assert_eq!(&vec!["x".to_string()], boxed_get.unwrap());
+    }
+
+    #[test]
+    fn error() {
+        let boxed_params = parse_with_duplicate_policy(QUERY, DuplicatePolicy::Error);
+        assert_eq!(Err(UrlSearchParamsError::DuplicateKey("a".to_string())), boxed_params);
+
+        let boxed_params = parse_with_duplicate_policy("a=1&b=2", DuplicatePolicy::Error);
+        assert!(boxed_params.is_ok());
+        assert_eq!(2, boxed_params.unwrap().len());
+    }
+}
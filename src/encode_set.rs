@@ -0,0 +1,261 @@
+use crate::{build_encode_table, encode_byte_into, is_percent_escape, HEX_UPPER};
+
+/// Set of bytes to be percent-encoded, with the way to encode whitespace
+///
+/// [EncodeSet::COMPONENT] is the default one, used by [encode_uri_component](crate::encode_uri_component),
+/// it encodes whitespace as `%20` as required by RFC 3986, which is expected
+/// by generic URI parsers and by signing schemes such as OAuth 1.0 or AWS
+/// Signature Version 4. [EncodeSet::FORM] encodes whitespace as `+`, the
+/// `application/x-www-form-urlencoded` way used by browsers submitting HTML
+/// forms and by JavaScript `URLSearchParams`, and expected by PHP, Python
+/// and most web frameworks parsing form data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeSet {
+    table: [bool; 256],
+    space_as_plus: bool,
+}
+
+impl EncodeSet {
+    /// Default encode set, see [ENCODE_TABLE](crate::ENCODE_TABLE), whitespace is encoded as `%20`
+    pub const COMPONENT: EncodeSet = EncodeSet {
+        table: build_encode_table(),
+        space_as_plus: false,
+    };
+
+    /// Same bytes as [EncodeSet::COMPONENT], but whitespace is encoded as `+`
+    pub const FORM: EncodeSet = EncodeSet {
+        table: build_encode_table(),
+        space_as_plus: true,
+    };
+
+    /// Check if given byte is percent-encoded by this set
+    pub const fn contains(&self, byte: u8) -> bool {
+        self.table[byte as usize]
+    }
+
+    /// Check if whitespace is encoded as `+` by this set
+    pub const fn space_as_plus(&self) -> bool {
+        self.space_as_plus
+    }
+}
+
+impl Default for EncodeSet {
+    fn default() -> Self {
+        EncodeSet::COMPONENT
+    }
+}
+
+/// Builder of a custom [EncodeSet]
+///
+/// Starts from the set encoding everything except RFC 3986 unreserved
+/// characters `A-Z a-z 0-9 - . _ ~`, unlike [EncodeSet::COMPONENT] question
+/// mark `?` is encoded too. Only ASCII characters can be allowed, bytes of
+/// non-ASCII characters as well as percent sign `%` are always encoded, so
+/// output can be decoded back. For the same reason plus sign `+` is always
+/// encoded if whitespace is encoded as `+`.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{encode_uri_component_with, EncodeSetBuilder};
+///
+/// let set = EncodeSetBuilder::new()
+///     .allow('(')
+///     .allow(')')
+///     .encode('~')
+///     .build();
+///
+/// assert_eq!(encode_uri_component_with("f(x)~y", &set), "f(x)%7Ey");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeSetBuilder {
+    set: EncodeSet,
+}
+
+impl EncodeSetBuilder {
+    /// Create builder allowing only RFC 3986 unreserved characters
+    pub fn new() -> EncodeSetBuilder {
+        let mut table = [true; 256];
+        for byte in 0..=255u8 {
+            table[byte as usize] = !matches!(byte, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~');
+        }
+        EncodeSetBuilder { set: EncodeSet { table, space_as_plus: false } }
+    }
+
+    /// Keep given ASCII character as is
+    pub fn allow(mut self, character: char) -> EncodeSetBuilder {
+        if character.is_ascii() && character != '%' {
+            self.set.table[character as usize] = false;
+        }
+        self
+    }
+
+    /// Percent-encode given ASCII character
+    pub fn encode(mut self, character: char) -> EncodeSetBuilder {
+        if character.is_ascii() {
+            self.set.table[character as usize] = true;
+        }
+        self
+    }
+
+    /// Encode whitespace as `+` instead of `%20`
+    pub fn space_as_plus(mut self, space_as_plus: bool) -> EncodeSetBuilder {
+        self.set.space_as_plus = space_as_plus;
+        self
+    }
+
+    pub fn build(mut self) -> EncodeSet {
+        if self.set.space_as_plus {
+            self.set.table[b'+' as usize] = true;
+        }
+        self.set
+    }
+}
+
+impl Default for EncodeSetBuilder {
+    fn default() -> Self {
+        EncodeSetBuilder::new()
+    }
+}
+
+/// Percent-encode given string using the given encode set
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{encode_uri_component_with, EncodeSet};
+///
+/// assert_eq!(encode_uri_component_with("a b+c", &EncodeSet::COMPONENT), "a%20b%2Bc");
+/// assert_eq!(encode_uri_component_with("a b+c", &EncodeSet::FORM), "a+b%2Bc");
+/// ```
+pub fn encode_uri_component_with(component: &str, set: &EncodeSet) -> String {
+    let mut result = String::with_capacity(component.len());
+    encode_uri_component_with_into(component, set, &mut result);
+    result
+}
+
+pub(crate) fn encode_uri_component_with_into(component: &str, set: &EncodeSet, out: &mut String) {
+    for byte in component.bytes() {
+        if byte == b' ' && set.space_as_plus {
+            out.push('+');
+        } else {
+            encode_byte_into(byte, &set.table, HEX_UPPER, out);
+        }
+    }
+}
+
+/// Check if given string is already fully encoded by the given encode set
+///
+/// Returns `true` only if every byte the set would encode is present as a
+/// valid `%XX` escape, so encoding the string again can be safely skipped.
+/// Literal `%` which is not a part of a valid escape makes it `false`. For
+/// sets encoding whitespace as `+`, literal `+` is considered encoded
+/// whitespace.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{is_fully_encoded, EncodeSet};
+///
+/// assert!(is_fully_encoded("a%20b%26c", &EncodeSet::COMPONENT));
+/// assert!(!is_fully_encoded("a b", &EncodeSet::COMPONENT));
+/// assert!(!is_fully_encoded("100%", &EncodeSet::COMPONENT));
+/// assert!(is_fully_encoded("a+b", &EncodeSet::FORM));
+/// ```
+pub fn is_fully_encoded(component: &str, set: &EncodeSet) -> bool {
+    let bytes = component.as_bytes();
+
+    let mut index = 0;
+    while index < bytes.len() {
+        if is_percent_escape(bytes, index) {
+            index += 3;
+            continue;
+        }
+
+        let byte = bytes[index];
+        let is_encoded_space = byte == b'+' && set.space_as_plus;
+        if set.contains(byte) && !is_encoded_space {
+            return false
+        }
+        index += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode_uri_component, encode_uri_component, encode_uri_component_with, is_fully_encoded, needs_encoding, EncodeSet, EncodeSetBuilder};
+
+    #[test]
+    fn component_matches_default_encoding() {
+        for byte in 0..=255u8 {
+            assert_eq!(needs_encoding(byte), EncodeSet::COMPONENT.contains(byte));
+            assert_eq!(needs_encoding(byte), EncodeSet::FORM.contains(byte));
+        }
+        assert_eq!(EncodeSet::COMPONENT, EncodeSet::default());
+
+        let component = "a b+c&d=ü";
+        assert_eq!(encode_uri_component(component), encode_uri_component_with(component, &EncodeSet::COMPONENT));
+    }
+
+    #[test]
+    fn form_encodes_space_as_plus() {
+        assert_eq!("a+b%2Bc%26d%3D%C3%BC", encode_uri_component_with("a b+c&d=ü", &EncodeSet::FORM));
+    }
+
+    #[test]
+    fn is_fully_encoded_test() {
+        let encoded = encode_uri_component("key=value & ключ");
+        assert!(is_fully_encoded(&encoded, &EncodeSet::COMPONENT));
+        assert!(is_fully_encoded("", &EncodeSet::COMPONENT));
+        assert!(is_fully_encoded("az~%2f", &EncodeSet::COMPONENT));
+
+        assert!(!is_fully_encoded("a b", &EncodeSet::COMPONENT));
+        assert!(!is_fully_encoded("%ZZ", &EncodeSet::COMPONENT));
+        assert!(!is_fully_encoded("%2", &EncodeSet::COMPONENT));
+        assert!(!is_fully_encoded("ü", &EncodeSet::COMPONENT));
+        assert!(!is_fully_encoded("a+b", &EncodeSet::COMPONENT));
+
+        let encoded = encode_uri_component_with("a b+c", &EncodeSet::FORM);
+        assert!(is_fully_encoded(&encoded, &EncodeSet::FORM));
+        assert!(!is_fully_encoded("a b", &EncodeSet::FORM));
+    }
+
+    #[test]
+    fn builder_allows_brackets() {
+        let set = EncodeSetBuilder::new().allow('(').allow(')').build();
+
+        assert_eq!("f(x)%20%3F", encode_uri_component_with("f(x) ?", &set));
+        assert_eq!("f%28x%29%20?", encode_uri_component("f(x) ?"));
+        assert_eq!("f(x) ?", decode_uri_component(&encode_uri_component_with("f(x) ?", &set)));
+    }
+
+    #[test]
+    fn builder_encodes_tilde() {
+        let set = EncodeSetBuilder::new().encode('~').space_as_plus(true).build();
+        assert_eq!("a%7Eb+c", encode_uri_component_with("a~b c", &set));
+    }
+
+    #[test]
+    fn builder_ignores_percent_and_non_ascii() {
+        let set = EncodeSetBuilder::default().allow('%').allow('ü').build();
+        assert_eq!("%25%C3%BC", encode_uri_component_with("%ü", &set));
+        assert_eq!(EncodeSetBuilder::new().build(), set);
+    }
+
+    #[test]
+    fn builder_keeps_plus_encoded_with_space_as_plus() {
+        for set in [
+            EncodeSetBuilder::new().allow('+').space_as_plus(true).build(),
+            EncodeSetBuilder::new().space_as_plus(true).allow('+').build(),
+        ] {
+            let encoded = encode_uri_component_with("a+b c", &set);
+            assert_eq!("a%2Bb+c", encoded);
+            assert_eq!("a+b c", decode_uri_component(&encoded.replace('+', " ")));
+        }
+
+        let set = EncodeSetBuilder::new().allow('+').build();
+        assert_eq!("a+b%20c", encode_uri_component_with("a+b c", &set));
+        assert_eq!("a+b c", decode_uri_component(&encode_uri_component_with("a+b c", &set)));
+    }
+}
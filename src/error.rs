@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned by fallible functions of the crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlSearchParamsError {
+    /// Malformed percent-encoded sequence, such as `%ZZ` or trailing `%`,
+    /// found at the given byte index of the input
+    Decode { input: String, index: usize },
+    /// Key-value pair which can not be parsed, such as one with empty key
+    MalformedPair(String),
+    /// Input exceeds the named limit, one of `"input bytes"`, `"pairs"`,
+    /// `"key bytes"` or `"value bytes"` of [ParseOptions](crate::ParseOptions),
+    /// or `"pair bytes"` of [chunk_params](crate::chunk_params)
+    LimitExceeded { limit: &'static str, max: usize },
+    /// Decoded bytes of the given input are not valid UTF-8
+    InvalidUtf8(String),
+    /// Decoded key appears more than once
+    DuplicateKey(String),
+}
+
+/// Error of the strict parsing functions, such as
+/// [try_parse_url_search_params](crate::try_parse_url_search_params)
+pub type ParseError = UrlSearchParamsError;
+
+impl fmt::Display for UrlSearchParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlSearchParamsError::Decode { input, index } =>
+                write!(f, "malformed percent-encoding at byte {} of {:?}", index, input),
+            UrlSearchParamsError::MalformedPair(pair) =>
+                write!(f, "malformed key-value pair {:?}", pair),
+            UrlSearchParamsError::LimitExceeded { limit, max } =>
+                write!(f, "{} limit of {} exceeded", limit, max),
+            UrlSearchParamsError::InvalidUtf8(input) =>
+                write!(f, "decoded {:?} is not valid UTF-8", input),
+            UrlSearchParamsError::DuplicateKey(key) =>
+                write!(f, "duplicate key {:?}", key),
+        }
+    }
+}
+
+impl Error for UrlSearchParamsError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::UrlSearchParamsError;
+
+    #[test]
+    fn display() {
+        let error = UrlSearchParamsError::Decode { input: "a%ZZ".to_string(), index: 1 };
+        assert_eq!("malformed percent-encoding at byte 1 of \"a%ZZ\"", error.to_string());
+
+        let error = UrlSearchParamsError::MalformedPair("=value".to_string());
+        assert_eq!("malformed key-value pair \"=value\"", error.to_string());
+
+        let error = UrlSearchParamsError::LimitExceeded { limit: "pairs", max: 100 };
+        assert_eq!("pairs limit of 100 exceeded", error.to_string());
+
+        let error = UrlSearchParamsError::InvalidUtf8("%FF".to_string());
+        assert_eq!("decoded \"%FF\" is not valid UTF-8", error.to_string());
+
+        let error = UrlSearchParamsError::DuplicateKey("a".to_string());
+        assert_eq!("duplicate key \"a\"", error.to_string());
+    }
+
+    #[test]
+    fn boxed_error() {
+        let result: Result<(), Box<dyn std::error::Error>> =
+            Err(UrlSearchParamsError::MalformedPair("a".to_string()).into());
+        assert!(result.is_err());
+    }
+}
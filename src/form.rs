@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use crate::{decode_uri_component, raw_pairs, EncodeSet};
+use crate::encode_set::encode_uri_component_with_into;
+
+/// How plus sign `+` is decoded in the form-parsing functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlusMode {
+    /// `+` is decoded as whitespace, as HTML forms encode it
+    #[default]
+    Space,
+    /// `+` is kept as is
+    Literal,
+}
+
+/// Options of [parse_form_with], plus sign is decoded as whitespace in both
+/// keys and values by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormOptions {
+    pub key_plus: PlusMode,
+    pub value_plus: PlusMode,
+}
+
+/// Convert given `application/x-www-form-urlencoded` string into a HashMap
+///
+/// Works as [parse_url_search_params](crate::parse_url_search_params), but
+/// plus sign `+` in keys and values is decoded as whitespace, encoded plus
+/// sign `%2B` is decoded as `+`.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_form;
+///
+/// let params = parse_form("q=rust+lang&sum=1%2B1");
+/// assert_eq!(params.get("q"), Some(&"rust lang".to_string()));
+/// assert_eq!(params.get("sum"), Some(&"1+1".to_string()));
+/// ```
+pub fn parse_form(params: &str) -> HashMap<String, String> {
+    parse_form_with(params, &FormOptions::default())
+}
+
+/// Convert given `application/x-www-form-urlencoded` string into a HashMap,
+/// decoding plus sign in keys and values according to the options
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_form_with, FormOptions, PlusMode};
+///
+/// let options = FormOptions { key_plus: PlusMode::Literal, value_plus: PlusMode::Space };
+/// let params = parse_form_with("a+b=c+d", &options);
+/// assert_eq!(params.get("a+b"), Some(&"c d".to_string()));
+/// ```
+pub fn parse_form_with(params: &str, options: &FormOptions) -> HashMap<String, String> {
+    let mut params_map : HashMap<String, String> = HashMap::new();
+
+    for (key, value) in raw_pairs(params) {
+        params_map.insert(decode_form_component(key, options.key_plus), decode_form_component(value, options.value_plus));
+    }
+    params_map
+}
+
+/// Convert given HashMap, with any hasher, into an
+/// `application/x-www-form-urlencoded` string
+///
+/// Works as [build_url_search_params](crate::build_url_search_params),
+/// including the order of parameters, but whitespace is encoded as `+`,
+/// see [EncodeSet::FORM].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::{build_form, parse_form};
+///
+/// let mut params_map: HashMap<String, String> = HashMap::new();
+/// params_map.insert("q".to_string(), "rust lang".to_string());
+/// params_map.insert("sum".to_string(), "1+1".to_string());
+///
+/// let form = build_form(params_map.clone());
+/// assert_eq!(form, "q=rust+lang&sum=1%2B1");
+/// assert_eq!(parse_form(&form), params_map);
+/// ```
+pub fn build_form<S: BuildHasher>(params: HashMap<String, String, S>) -> String {
+    let mut key_value_list : Vec<String> = vec![];
+    for (key, value) in params {
+        let mut param = String::new();
+        encode_uri_component_with_into(&key, &EncodeSet::FORM, &mut param);
+        param.push('=');
+        encode_uri_component_with_into(&value, &EncodeSet::FORM, &mut param);
+        key_value_list.push(param);
+    }
+
+    key_value_list.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
+    key_value_list.join("&")
+}
+
+pub(crate) fn decode_form_component(component: &str, plus: PlusMode) -> String {
+    match plus {
+        PlusMode::Space => decode_uri_component(&component.replace('+', " ")),
+        PlusMode::Literal => decode_uri_component(component),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{build_form, parse_form, parse_form_with, FormOptions, PlusMode};
+
+    #[test]
+    fn parse_form_default() {
+        let params = parse_form("a+b=c+d&e=%2B+%20");
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("a b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "c d");
+
+        let boxed_get = params.get("e");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "+  ");
+    }
+
+    #[test]
+    fn parse_form_literal_key_plus() {
+        let options = FormOptions { key_plus: PlusMode::Literal, value_plus: PlusMode::Space };
+        let params = parse_form_with("a+b=c+d", &options);
+
+        let boxed_get = params.get("a+b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "c d");
+    }
+
+    #[test]
+    fn parse_form_literal_value_plus() {
+        let options = FormOptions { key_plus: PlusMode::Space, value_plus: PlusMode::Literal };
+        let params = parse_form_with("a+b=c+d", &options);
+
+        let boxed_get = params.get("a b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "c+d");
+    }
+
+    #[test]
+    fn build_form_round_trip() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("a b".to_string(), "c d".to_string());
+        params_map.insert("e".to_string(), "+ &=".to_string());
+        params_map.insert("empty".to_string(), "".to_string());
+
+        let form = build_form(params_map.clone());
+        assert_eq!("a+b=c+d&e=%2B+%26%3D&empty=", form);
+        assert_eq!(params_map, parse_form(&form));
+
+        assert_eq!("", build_form(HashMap::new()));
+    }
+}
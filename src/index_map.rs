@@ -0,0 +1,72 @@
+use indexmap::IndexMap;
+use crate::{build_from_pairs, decode_uri_component, raw_pairs};
+
+/// Convert given string into an IndexMap containing query string parameters,
+/// in the order they appear
+///
+/// Same as [parse_url_search_params](crate::parse_url_search_params), the
+/// last value of a repeated key wins, but the key keeps the position of its
+/// first appearance.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_url_search_params_indexed;
+///
+/// let params = parse_url_search_params_indexed("b=1&a=2&b=3");
+///
+/// let pairs: Vec<(&String, &String)> = params.iter().collect();
+/// assert_eq!(pairs, vec![(&"b".to_string(), &"3".to_string()), (&"a".to_string(), &"2".to_string())]);
+/// ```
+pub fn parse_url_search_params_indexed(params: &str) -> IndexMap<String, String> {
+    raw_pairs(params)
+        .map(|(key, value)| (decode_uri_component(key), decode_uri_component(value)))
+        .collect()
+}
+
+/// Convert given IndexMap into a query string, keeping insertion order
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{build_url_search_params_indexed, parse_url_search_params_indexed};
+///
+/// let mut params = parse_url_search_params_indexed("z=1&a=2");
+/// params.insert("m".to_string(), "x y".to_string());
+///
+/// assert_eq!(build_url_search_params_indexed(params), "z=1&a=2&m=x%20y");
+/// ```
+pub fn build_url_search_params_indexed(params: IndexMap<String, String>) -> String {
+    build_from_pairs(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use crate::{build_url_search_params_indexed, parse_url_search_params_indexed};
+
+    #[test]
+    fn round_trip_keeps_order() {
+        let query = "z=last%20letter&a%26b=c%3Dd&m=1&=skipped";
+        let mut params = parse_url_search_params_indexed(query);
+        assert_eq!(3, params.len());
+
+        let keys: Vec<&str> = params.keys().map(|key| key.as_str()).collect();
+        assert_eq!(vec!["z", "a&b", "m"], keys);
+
+        let boxed_get = params.get_mut("m");
+        assert!(boxed_get.is_some());
+        *boxed_get.unwrap() = "2".to_string();
+        params.insert("new".to_string(), "".to_string());
+
+        assert_eq!("z=last%20letter&a%26b=c%3Dd&m=2&new=", build_url_search_params_indexed(params));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let params = parse_url_search_params_indexed("a=1&b=2&a=3");
+        assert_eq!("a=3&b=2", build_url_search_params_indexed(params));
+
+        assert_eq!("", build_url_search_params_indexed(IndexMap::new()));
+    }
+}
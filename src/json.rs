@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+
+/// Parse value of the given key as JSON
+///
+/// Returns `None` if there is no such key in the map. Map values are expected
+/// to be already decoded, as returned by [parse_url_search_params](crate::parse_url_search_params).
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::{get_json, parse_url_search_params};
+///
+/// let params: HashMap<String, String> = parse_url_search_params("ids=%5B1%2C2%2C3%5D");
+///
+/// let boxed_ids: Option<Result<Vec<u32>, serde_json::Error>> = get_json(&params, "ids");
+/// assert_eq!(boxed_ids.unwrap().unwrap(), vec![1, 2, 3]);
+/// ```
+pub fn get_json<T: DeserializeOwned>(map: &HashMap<String, String>, key: &str) -> Option<Result<T, serde_json::Error>> {
+    map.get(key).map(|value| serde_json::from_str(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{build_url_search_params, get_json, parse_url_search_params};
+
+    #[test]
+    fn get_json_object() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("filter".to_string(), "{\"price\": 10, \"color\": 20}".to_string());
+
+        let search_params = build_url_search_params(params_map);
+        let parsed_search_params = parse_url_search_params(&search_params);
+
+        let boxed_get : Option<Result<HashMap<String, u32>, serde_json::Error>> = get_json(&parsed_search_params, "filter");
+        assert!(boxed_get.is_some());
+
+        let filter = boxed_get.unwrap().unwrap();
+        assert_eq!(2, filter.len());
+        assert_eq!(10, filter["price"]);
+        assert_eq!(20, filter["color"]);
+    }
+
+    #[test]
+    fn get_json_missing_key() {
+        let parsed_search_params = parse_url_search_params("a=1");
+
+        let boxed_get : Option<Result<u32, serde_json::Error>> = get_json(&parsed_search_params, "b");
+        assert!(boxed_get.is_none());
+    }
+
+    #[test]
+    fn get_json_invalid() {
+        let parsed_search_params = parse_url_search_params("a=%7Bnot_json");
+
+        let boxed_get : Option<Result<serde_json::Value, serde_json::Error>> = get_json(&parsed_search_params, "a");
+        assert!(boxed_get.is_some());
+        assert!(boxed_get.unwrap().is_err());
+    }
+}
@@ -10,12 +10,72 @@
 //! Also hash mark `#` url delimiter and fragment part of URL is not the parts of a query string.
 //! In practice, it means, the fragment and preceding hash mark won't be sent in a request to a server.
 //!
-use std::collections::HashMap;
+//! Optional `json` feature adds `get_json` to parse query string values holding JSON.
+//! Optional `indexmap` feature adds parse and build functions keeping parameters in insertion order.
+//! Optional `encoding_rs` feature adds support of legacy charsets.
+//!
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::BuildHasher;
+
+pub mod error;
+
+mod builder;
+mod bytes;
+mod canonical;
+mod diff;
+mod duplicates;
+mod encode_set;
+mod form;
+#[macro_use]
+mod macros;
+mod nested;
+mod options;
+mod os_str;
+mod params;
+mod report;
+
+#[cfg(feature = "encoding_rs")]
+mod charset;
+#[cfg(feature = "indexmap")]
+mod index_map;
+#[cfg(feature = "json")]
+mod json;
+
+pub use builder::QueryStringBuilder;
+pub use bytes::{decode_uri_component_bytes, parse_bytes, parse_bytes_ordered, parse_bytes_raw};
+pub use canonical::{canonicalize, canonicalize_with, reorder_like, secure_query_eq, sort_query, CanonicalQuery, CanonicalizeOptions};
+pub use diff::{diff, ChangedParam, QueryDiff};
+pub use duplicates::{parse_with_duplicate_policy, DuplicatePolicy};
+pub use encode_set::{encode_uri_component_with, is_fully_encoded, EncodeSet, EncodeSetBuilder};
+pub use error::{ParseError, UrlSearchParamsError};
+pub use form::{build_form, parse_form, parse_form_with, FormOptions, PlusMode};
+pub use nested::{parse_nested, NestedValue};
+pub use options::{DecodeMode, ParseOptions, Utf8Policy};
+pub use os_str::{decode_os_string, encode_os_str};
+pub use params::{MergeStrategy, ParamEntry, UrlSearchParams};
+pub use report::{parse_with_report, ParseIssue};
+
+#[cfg(feature = "encoding_rs")]
+pub use charset::{decode_uri_component_charset, encode_uri_component_charset, parse_url_search_params_charset, sniff_charset};
+#[cfg(feature = "indexmap")]
+pub use index_map::{build_url_search_params_indexed, parse_url_search_params_indexed};
+#[cfg(feature = "json")]
+pub use json::get_json;
 
 
 /// Convert given string into a HashMap containing query string parameters as
 /// key-value pairs
 ///
+/// Each pair is split on the first `=`, so `a==b` yields value `=b`, and
+/// `a=` as well as `a` yield an empty value. Pairs with empty keys are
+/// skipped, for duplicate keys the last value wins.
+///
+/// Keys and values are decoded exactly once, so `a=%2525` yields value
+/// `%25`. Do not pass already decoded query string, literal `%` in it
+/// would be decoded again.
+///
 /// # Examples
 ///
 /// ```
@@ -43,36 +103,561 @@ use std::collections::HashMap;
 pub fn parse_url_search_params(params: &str) -> HashMap<String, String> {
     let mut params_map : HashMap<String, String> = HashMap::new();
 
-    if params.trim().is_empty() {
-        return params_map
+    for (key, value) in raw_pairs(params) {
+        params_map.insert(decode_uri_component(key), decode_uri_component(value));
+    }
+    params_map
+}
+
+/// Convert given string into a BTreeMap containing query string parameters
+///
+/// Same as [parse_url_search_params], the last value of a repeated key
+/// wins, but keys are ordered bytewise, so iteration order is stable.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_url_search_params_btree;
+///
+/// let params = parse_url_search_params_btree("b=2&a=1&B=3");
+/// let keys: Vec<&String> = params.keys().collect();
+/// assert_eq!(keys, vec!["B", "a", "b"]);
+/// ```
+pub fn parse_url_search_params_btree(params: &str) -> BTreeMap<String, String> {
+    raw_pairs(params)
+        .map(|(key, value)| (decode_uri_component(key), decode_uri_component(value)))
+        .collect()
+}
+
+/// Convert given string into key-value pairs, in the order they appear
+///
+/// Unlike [parse_url_search_params], duplicate keys are kept, so the
+/// original query string can be reproduced or verified. Pairs with empty
+/// keys are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_url_search_params_ordered;
+///
+/// let pairs = parse_url_search_params_ordered("b=1&a=hello%20world&b=2");
+/// assert_eq!(pairs, vec![
+///     ("b".to_string(), "1".to_string()),
+///     ("a".to_string(), "hello world".to_string()),
+///     ("b".to_string(), "2".to_string()),
+/// ]);
+/// ```
+pub fn parse_url_search_params_ordered(params: &str) -> Vec<(String, String)> {
+    let mut pairs : Vec<(String, String)> = vec![];
+    parse_into(params, &mut pairs);
+    pairs
+}
+
+/// Convert given string into a HashMap, where flag parameters without `=`
+/// have no value
+///
+/// Tells `foo` (`None`) apart from `foo=` (`Some("")`), which
+/// [parse_url_search_params] does not. The last value of a repeated key
+/// wins, pairs with empty keys are skipped. Counterpart of
+/// [build_url_search_params_optional].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::parse_url_search_params_optional;
+///
+/// let params: HashMap<String, Option<String>> = parse_url_search_params_optional("foo&bar=&baz=1");
+///
+/// assert_eq!(params.get("foo"), Some(&None));
+/// assert_eq!(params.get("bar"), Some(&Some("".to_string())));
+/// assert_eq!(params.get("baz"), Some(&Some("1".to_string())));
+/// ```
+pub fn parse_url_search_params_optional(params: &str) -> HashMap<String, Option<String>> {
+    raw_optional_pairs(params)
+        .map(|(key, value)| (decode_uri_component(key), value.map(decode_uri_component)))
+        .collect()
+}
+
+/// Convert given string into keys with all of their values
+///
+/// Values are kept in the order they appear, so `tag=a&tag=b` gives
+/// `["a", "b"]` for `tag`. Pairs with empty keys are skipped. See
+/// [parse_grouped_ordered] if order of the keys matters as well.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::parse_url_search_params_multi;
+///
+/// let params: HashMap<String, Vec<String>> = parse_url_search_params_multi("tag=a&id=1&tag=b");
+///
+/// let boxed_get = params.get("tag");
+/// assert!(boxed_get.is_some());
+/// assert_eq!(boxed_get.unwrap(), &vec!["a".to_string(), "b".to_string()]);
+/// ```
+pub fn parse_url_search_params_multi(params: &str) -> HashMap<String, Vec<String>> {
+    let mut params_map : HashMap<String, Vec<String>> = HashMap::new();
+
+    for (key, value) in raw_pairs(params) {
+        params_map.entry(decode_uri_component(key))
+            .or_default()
+            .push(decode_uri_component(value));
+    }
+    params_map
+}
+
+/// Convert given string into key-value pairs, in the order they appear,
+/// reusing the given Vec
+///
+/// The Vec is cleared first, its capacity is kept, so it can be reused
+/// between calls. Pairs are decoded the same way [parse_url_search_params]
+/// does, pairs with empty keys are skipped, duplicate keys are kept.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_into;
+///
+/// let mut pairs: Vec<(String, String)> = Vec::with_capacity(16);
+///
+/// parse_into("b=1&a=hello%20world", &mut pairs);
+/// assert_eq!(pairs, vec![
+///     ("b".to_string(), "1".to_string()),
+///     ("a".to_string(), "hello world".to_string()),
+/// ]);
+///
+/// parse_into("c=3", &mut pairs);
+/// assert_eq!(pairs, vec![("c".to_string(), "3".to_string())]);
+/// ```
+pub fn parse_into(params: &str, out: &mut Vec<(String, String)>) {
+    out.clear();
+
+    for (key, value) in raw_pairs(params) {
+        out.push((decode_uri_component(key), decode_uri_component(value)));
     }
+}
+
+/// Convert given string into keys with all of their values, keys are kept
+/// in the order they first appear, values in the order they appear
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_grouped_ordered;
+///
+/// let groups = parse_grouped_ordered("b=1&a=2&b=3");
+/// assert_eq!(groups, vec![
+///     ("b".to_string(), vec!["1".to_string(), "3".to_string()]),
+///     ("a".to_string(), vec!["2".to_string()]),
+/// ]);
+/// ```
+pub fn parse_grouped_ordered(params: &str) -> Vec<(String, Vec<String>)> {
+    let mut groups : Vec<(String, Vec<String>)> = vec![];
+    let mut positions : HashMap<String, usize> = HashMap::new();
 
-    let split_iter = params.split("&").into_iter();
-    for param in split_iter {
-        let mut key = "";
-        let mut value = "";
+    for (key, value) in raw_pairs(params) {
+        let key = decode_uri_component(key);
+        let value = decode_uri_component(value);
 
-        let mut key_value = param.split("=").into_iter();
-        let boxed_key = key_value.next();
-        if boxed_key.is_some() {
-            key = boxed_key.unwrap();
+        match positions.get(&key) {
+            Some(position) => groups[*position].1.push(value),
+            None => {
+                positions.insert(key.to_string(), groups.len());
+                groups.push((key, vec![value]));
+            }
         }
+    }
+    groups
+}
+
+/// Convert given string into key-value pairs, in the order they appear,
+/// decoding keys and values with the given function
+///
+/// Splitting into pairs works the same way as in [parse_url_search_params],
+/// only the decoding step is replaced.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_with_decoder;
+///
+/// let pairs = parse_with_decoder("a=%20&b=c%26", |raw| raw.to_string());
+/// assert_eq!(pairs, vec![
+///     ("a".to_string(), "%20".to_string()),
+///     ("b".to_string(), "c%26".to_string()),
+/// ]);
+/// ```
+pub fn parse_with_decoder(params: &str, decode: impl Fn(&str) -> String) -> Vec<(String, String)> {
+    raw_pairs(params)
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect()
+}
+
+/// Convert given matrix parameters of a single URL path segment into a HashMap
+///
+/// Matrix parameters are appended to a path segment and separated by
+/// semicolons, like `/shirts;color=red;size=xl/details`. Pass only the
+/// parameters of one segment, `color=red;size=xl` in this case: the segment
+/// name before the first semicolon is not a parameter and slashes are not
+/// handled. Parameters are decoded the same way [parse_url_search_params]
+/// does.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_matrix_params;
+///
+/// let params = parse_matrix_params("color=red;size=xl");
+/// assert_eq!(params.get("color"), Some(&"red".to_string()));
+/// assert_eq!(params.get("size"), Some(&"xl".to_string()));
+/// ```
+pub fn parse_matrix_params(segment: &str) -> HashMap<String, String> {
+    let mut params_map : HashMap<String, String> = HashMap::new();
+
+    for (key, value) in raw_pairs_separated_by(segment, &[';']) {
+        params_map.insert(decode_uri_component(key), decode_uri_component(value));
+    }
+    params_map
+}
+
+/// Convert given string into a HashMap containing query string parameters,
+/// failing on malformed input
+///
+/// By default it works as [parse_url_search_params], the last value of a
+/// repeated key wins, but keys and values are decoded via
+/// [try_decode_uri_component]. Options change it as follows:
+///
+/// - [ParseOptions::strip_question_mark] strips one leading `?`.
+/// - [ParseOptions::html_entities] replaces HTML entities of `&` with it.
+/// - [ParseOptions::separators] sets the characters separating pairs.
+/// - [ParseOptions::reject_empty_keys] fails on pairs with empty keys instead of skipping them.
+/// - [ParseOptions::decode_mode] set to [DecodeMode::Lossy] decodes via [decode_uri_component].
+/// - [ParseOptions::plus_as_space] decodes `+` as whitespace.
+/// - [ParseOptions::utf8_policy] sets how invalid UTF-8 is handled.
+/// - [ParseOptions::aliases] renames keys to their canonical names.
+/// - [ParseOptions::reject_duplicates] fails on a repeated key.
+///
+/// Limits fail with [UrlSearchParamsError::LimitExceeded], set them when
+/// parsing untrusted input. [ParseOptions::max_len] is checked first, before
+/// the input is otherwise touched. Pairs are then split one by one, each
+/// counted toward [ParseOptions::max_pairs] and checked against
+/// [ParseOptions::max_key_len] and [ParseOptions::max_value_len] before it
+/// is decoded. Note that unescaping of HTML entities is done over the whole
+/// input before splitting.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{try_parse_url_search_params, ParseOptions, UrlSearchParamsError};
+///
+/// let params = try_parse_url_search_params("a=1&a=2", &ParseOptions::default()).unwrap();
+/// assert_eq!(params.get("a"), Some(&"2".to_string()));
+///
+/// let options = ParseOptions { reject_duplicates: true, ..ParseOptions::default() };
+/// let boxed_params = try_parse_url_search_params("a=1&a=2", &options);
+/// assert_eq!(boxed_params, Err(UrlSearchParamsError::DuplicateKey("a".to_string())));
+/// ```
+pub fn try_parse_url_search_params(params: &str, options: &ParseOptions) -> Result<HashMap<String, String>, UrlSearchParamsError> {
+    check_limit(params.len(), options.max_len, "input bytes")?;
+
+    let params = match params.strip_prefix('?') {
+        Some(stripped) if options.strip_question_mark => stripped,
+        _ => params,
+    };
+    let unescaped : String;
+    let params = if options.html_entities {
+        unescaped = unescape_ampersands(params);
+        unescaped.as_str()
+    } else {
+        params
+    };
+
+    let mut params_map : HashMap<String, String> = HashMap::new();
 
-        let boxed_value = key_value.next();
-        if boxed_value.is_some() {
-            value = boxed_value.unwrap();
+    for (index, pair) in raw_segments_separated_by(params, &options.separators).enumerate() {
+        check_limit(index + 1, options.max_pairs, "pairs")?;
+
+        let (key, value) = pair.split_once('=').unwrap_or((pair, SYMBOL.empty_string));
+        if key.is_empty() {
+            if options.reject_empty_keys {
+                return Err(UrlSearchParamsError::MalformedPair(pair.to_string()))
+            }
+            continue;
         }
+        check_limit(key.len(), options.max_key_len, "key bytes")?;
+        check_limit(value.len(), options.max_value_len, "value bytes")?;
+
+        let key = options.resolve_alias(options.decode(key)?);
+        let value = options.decode(value)?;
 
-        if !key.is_empty() {
-            params_map.insert(decode_uri_component(key), decode_uri_component(value));
+        if options.reject_duplicates && params_map.contains_key(&key) {
+            return Err(UrlSearchParamsError::DuplicateKey(key))
         }
+        params_map.insert(key, value);
+    }
+    Ok(params_map)
+}
 
+/// Fail with [UrlSearchParamsError::LimitExceeded] if the value is over the limit, if any
+fn check_limit(value: usize, max: Option<usize>, limit: &'static str) -> Result<(), UrlSearchParamsError> {
+    match max {
+        Some(max) if value > max => Err(UrlSearchParamsError::LimitExceeded { limit, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Replace HTML entities of ampersand, `&amp;`, `&#38;` and `&#x26;`, with `&`
+///
+/// Numeric entities may be zero-padded, like `&#038;` or `&#x026;`. Input is
+/// scanned once from left to right, so `&amp;#38;` becomes literal `&#38;`.
+fn unescape_ampersands(params: &str) -> String {
+    let mut result = String::with_capacity(params.len());
+    let mut rest = params;
+
+    while let Some(position) = rest.find('&') {
+        result.push_str(&rest[..position]);
+        result.push('&');
+        rest = &rest[position + 1..];
+        rest = &rest[ampersand_entity_len(rest)..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Length of the rest of an ampersand entity, `amp;`, `#0*38;` or
+/// `#[xX]0*26;`, at the start of given string, 0 if there is none
+fn ampersand_entity_len(rest: &str) -> usize {
+    if rest.starts_with("amp;") {
+        return "amp;".len()
+    }
+
+    let Some(number) = rest.strip_prefix('#') else {
+        return 0
+    };
+    let (digits, code) = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => (hex, "26;"),
+        None => (number, "38;"),
+    };
+    let unpadded = digits.trim_start_matches('0');
+
+    if unpadded.starts_with(code) {
+        rest.len() - unpadded.len() + code.len()
+    } else {
+        0
     }
-    params_map
 }
 
+/// Split given query string into still encoded key-value pairs, pairs with
+/// empty key are skipped
+///
+/// Each pair is split on the first `=` only, the rest of it belongs to the
+/// value, so `a==b` yields key `a` and value `=b`.
+fn raw_pairs(params: &str) -> impl Iterator<Item = (&str, &str)> {
+    raw_pairs_separated_by(params, &['&'])
+}
+
+/// Same as [raw_pairs], but pairs are separated by any of the given characters
+fn raw_pairs_separated_by<'a>(params: &'a str, separators: &'a [char]) -> impl Iterator<Item = (&'a str, &'a str)> {
+    raw_optional_pairs_separated_by(params, separators)
+        .map(|(key, value)| (key, value.unwrap_or(SYMBOL.empty_string)))
+}
+
+/// Same as [raw_pairs], but value is `None` if there is no `=` in the pair,
+/// so flag `a` can be told apart from `a=`
+fn raw_optional_pairs(params: &str) -> impl Iterator<Item = (&str, Option<&str>)> {
+    raw_optional_pairs_separated_by(params, &['&'])
+}
+
+/// Same as [raw_optional_pairs], but pairs are separated by any of the
+/// given characters
+fn raw_optional_pairs_separated_by<'a>(params: &'a str, separators: &'a [char]) -> impl Iterator<Item = (&'a str, Option<&'a str>)> {
+    raw_segments_separated_by(params, separators)
+        .filter_map(|param| {
+            let mut key_value = param.splitn(2, '=');
+            let key = key_value.next().unwrap_or(SYMBOL.empty_string);
+            let value = key_value.next();
+
+            if key.is_empty() {
+                None
+            } else {
+                Some((key, value))
+            }
+        })
+}
+
+/// Split given query string into non-empty, still encoded segments, blank
+/// input yields none
+///
+/// Segments are not split into key and value, so ones with empty key, such
+/// as `=value`, are kept.
+fn raw_segments_separated_by<'a>(params: &'a str, separators: &'a [char]) -> impl Iterator<Item = &'a str> {
+    let is_blank = params.trim().is_empty();
+
+    params.split(separators)
+        .filter(move |param| !is_blank && !param.is_empty())
+}
+
+/// Get decoded key-value pair at the given position of the query string
+///
+/// Position is counted among parameters with non-empty keys, the same ones
+/// [parse_url_search_params] returns. Query string is scanned only up to
+/// the requested position.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::nth_param;
+///
+/// let search_params = "a=1&b=hello%20world&c=3";
+///
+/// let boxed_param = nth_param(search_params, 1);
+/// assert_eq!(boxed_param, Some(("b".to_string(), "hello world".to_string())));
+///
+/// let boxed_param = nth_param(search_params, 3);
+/// assert!(boxed_param.is_none());
+/// ```
+pub fn nth_param(query: &str, index: usize) -> Option<(String, String)> {
+    raw_pairs(query)
+        .nth(index)
+        .map(|(key, value)| (decode_uri_component(key), decode_uri_component(value)))
+}
+
+
+/// Iterate over decoded values of the given key, in the order they appear
+///
+/// Values of other keys are not decoded.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::values_for;
+///
+/// let values: Vec<String> = values_for("t=a&x=1&t=b", "t").collect();
+/// assert_eq!(values, vec!["a", "b"]);
+/// ```
+pub fn values_for<'a>(query: &'a str, key: &str) -> impl Iterator<Item = String> + 'a {
+    let key = key.to_string();
+
+    raw_pairs(query)
+        .filter(move |(raw_key, _)| decode_uri_component(raw_key) == key)
+        .map(|(_, value)| decode_uri_component(value))
+}
+
+
+/// Get the first still encoded value of the given key, borrowed from the
+/// query string
+///
+/// Keys are compared as is, without decoding, so `a%20b` does not match
+/// key `a b`. Nothing is allocated, decode the value via
+/// [decode_uri_component] if needed.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::first_raw;
+///
+/// let search_params = "q=hello%20world&q=again";
+/// assert_eq!(first_raw(search_params, "q"), Some("hello%20world"));
+/// assert_eq!(first_raw(search_params, "page"), None);
+/// ```
+pub fn first_raw<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    raw_pairs(query)
+        .find(|(raw_key, _)| *raw_key == key)
+        .map(|(_, value)| value)
+}
+
+/// Keep only the first `n` parameters of the query string
+///
+/// Query string is scanned only up to the `n`-th parameter, kept parameters
+/// are decoded and built back via [build_url_search_params_ordered].
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::take_params;
+///
+/// assert_eq!(take_params("a=1&b=2&c=3", 2), "a=1&b=2");
+/// assert_eq!(take_params("a=1", 5), "a=1");
+/// assert_eq!(take_params("a=1", 0), "");
+/// ```
+pub fn take_params(query: &str, n: usize) -> String {
+    let pairs : Vec<(String, String)> = raw_pairs(query)
+        .take(n)
+        .map(|(key, value)| (decode_uri_component(key), decode_uri_component(value)))
+        .collect();
+
+    build_url_search_params_ordered(&pairs)
+}
+
+/// Check if any decoded key-value pair of the query string matches the predicate
+///
+/// Pairs are decoded one by one and scanning stops at the first match.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::any;
+///
+/// assert!(any("a=1&b=&c=3", |_, value| value.is_empty()));
+/// assert!(!any("a=1&b=2", |key, value| key == "a" && value == "2"));
+/// ```
+pub fn any(query: &str, pred: impl Fn(&str, &str) -> bool) -> bool {
+    raw_pairs(query)
+        .any(|(key, value)| pred(&decode_uri_component(key), &decode_uri_component(value)))
+}
 
-/// Convert given HashMap into a query string
+
+/// Get a set of decoded keys of the query string, values are not decoded
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use url_search_params::key_set;
+///
+/// let keys: HashSet<String> = key_set("a=1&b=2&a=3");
+/// assert_eq!(keys.len(), 2);
+/// assert!(keys.contains("a"));
+/// assert!(keys.contains("b"));
+/// ```
+pub fn key_set(query: &str) -> HashSet<String> {
+    raw_pairs(query)
+        .map(|(key, _)| decode_uri_component(key))
+        .collect()
+}
+
+
+/// Get a set of decoded keys of flag parameters, the ones without `=`
+///
+/// In `debug&verbose=1&empty=` only `debug` is a flag, `empty` has an
+/// explicitly empty value. Parsing functions treat flags as parameters with
+/// empty value, use this function to tell them apart, or
+/// [UrlSearchParams], which keeps flags.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use url_search_params::flag_set;
+///
+/// let flags: HashSet<String> = flag_set("debug&verbose=1&empty=");
+/// assert_eq!(flags.len(), 1);
+/// assert!(flags.contains("debug"));
+/// ```
+pub fn flag_set(query: &str) -> HashSet<String> {
+    raw_optional_pairs(query)
+        .filter(|(_, value)| value.is_none())
+        .map(|(key, _)| decode_uri_component(key))
+        .collect()
+}
+
+/// Convert given HashMap, with any hasher, into a query string
+///
+/// Parameters are sorted case-insensitively, parameters equal regardless of
+/// case are ordered bytewise, so the output does not depend on HashMap
+/// iteration order.
 ///
 /// # Examples
 ///
@@ -104,7 +689,7 @@ pub fn parse_url_search_params(params: &str) -> HashMap<String, String> {
 ///
 ///
 /// ```
-pub fn build_url_search_params(params: HashMap<String, String>) -> String {
+pub fn build_url_search_params<S: BuildHasher>(params: HashMap<String, String, S>) -> String {
 
     let mut key_value_list : Vec<String> = vec![];
     for (key, value) in params {
@@ -112,235 +697,2543 @@ pub fn build_url_search_params(params: HashMap<String, String>) -> String {
         key_value_list.push(param);
     }
 
-    key_value_list.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+    key_value_list.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
     let url_search_params : String = key_value_list.join("&");
 
     url_search_params
 }
 
-pub fn encode_uri_component(component: &str) -> String {
-    let mut _result = component.replace(SYMBOL.percent, "%25");
-    _result = _result.replace(SYMBOL.whitespace, "%20");
-    _result = _result.replace(SYMBOL.carriage_return, "%0D");
-    _result = _result.replace(SYMBOL.new_line, "%0A");
-    _result = _result.replace(SYMBOL.exclamation_mark, "%21");
-    _result = _result.replace(SYMBOL.quotation_mark, "%22");
-    _result = _result.replace(SYMBOL.number_sign, "%23");
-    _result = _result.replace(SYMBOL.dollar, "%24");
-    _result = _result.replace(SYMBOL.ampersand, "%26");
-    _result = _result.replace(SYMBOL.single_quote, "%27");
-    _result = _result.replace(SYMBOL.opening_bracket, "%28");
-    _result = _result.replace(SYMBOL.closing_bracket, "%29");
-    _result = _result.replace(SYMBOL.asterisk, "%2A");
-    _result = _result.replace(SYMBOL.plus, "%2B");
-    _result = _result.replace(SYMBOL.comma, "%2C");
-    _result = _result.replace(SYMBOL.slash, "%2F");
-    _result = _result.replace(SYMBOL.colon, "%3A");
-    _result = _result.replace(SYMBOL.semicolon, "%3B");
-    _result = _result.replace(SYMBOL.equals, "%3D");
-    _result = _result.replace(SYMBOL.at, "%40");
-    _result = _result.replace(SYMBOL.opening_square_bracket, "%5B");
-    _result = _result.replace(SYMBOL.closing_square_bracket, "%5D");
-
-
-    return _result
+/// Convert given HashMap, with any hasher, into a query string, ordering
+/// parameters with the given comparator of key-value pairs
+///
+/// Use it instead of [build_url_search_params] when an external API
+/// requires a specific order, for example bytewise ordering of keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::build_url_search_params_by;
+///
+/// let mut params_map: HashMap<String, String> = HashMap::new();
+/// params_map.insert("b".to_string(), "1".to_string());
+/// params_map.insert("C".to_string(), "2".to_string());
+///
+/// let search_params = build_url_search_params_by(params_map, |(a, _), (b, _)| a.cmp(b));
+/// assert_eq!(search_params, "C=2&b=1");
+/// ```
+pub fn build_url_search_params_by<S, F>(params: HashMap<String, String, S>, mut compare: F) -> String
+    where S: BuildHasher, F: FnMut((&str, &str), (&str, &str)) -> Ordering {
+    let mut pairs : Vec<(String, String)> = params.into_iter().collect();
+    pairs.sort_by(|(a_key, a_value), (b_key, b_value)| compare((a_key, a_value), (b_key, b_value)));
+
+    build_from_pairs(pairs)
 }
 
-pub fn decode_uri_component(component: &str) -> String {
-    let mut _result = component.replace( "%20", SYMBOL.whitespace);
-    _result = _result.replace("%0A", SYMBOL.new_line);
-    _result = _result.replace ("%0D", SYMBOL.carriage_return);
-    _result = _result.replace ("%21", SYMBOL.exclamation_mark);
-    _result = _result.replace ("%22", SYMBOL.quotation_mark);
-    _result = _result.replace ("%23", SYMBOL.number_sign);
-    _result = _result.replace ("%24", SYMBOL.dollar);
-    _result = _result.replace ("%25", SYMBOL.percent);
-    _result = _result.replace ("%26", SYMBOL.ampersand);
-    _result = _result.replace ("%27", SYMBOL.single_quote);
-    _result = _result.replace ("%28", SYMBOL.opening_bracket);
-    _result = _result.replace ("%29", SYMBOL.closing_bracket);
-    _result = _result.replace ("%2A", SYMBOL.asterisk);
-    _result = _result.replace ("%2B", SYMBOL.plus);
-    _result = _result.replace ("%2C", SYMBOL.comma);
-    _result = _result.replace ("%2F", SYMBOL.slash);
-    _result = _result.replace ("%3A", SYMBOL.colon);
-    _result = _result.replace ("%3B", SYMBOL.semicolon);
-    _result = _result.replace ("%3D", SYMBOL.equals);
-    _result = _result.replace ("%3F", SYMBOL.question_mark);
-    _result = _result.replace ("%40", SYMBOL.at);
-    _result = _result.replace ("%5B", SYMBOL.opening_square_bracket);
-    _result = _result.replace ("%5D", SYMBOL.closing_square_bracket);
-
-    return _result
+/// Convert given BTreeMap into a query string
+///
+/// Parameters are kept in the map order, which is bytewise by key, unlike
+/// the case-insensitive order of [build_url_search_params], so the output
+/// is deterministic across runs and platforms.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use url_search_params::build_url_search_params_btree;
+///
+/// let mut params: BTreeMap<String, String> = BTreeMap::new();
+/// params.insert("b".to_string(), "2".to_string());
+/// params.insert("B".to_string(), "x y".to_string());
+/// params.insert("a".to_string(), "1".to_string());
+///
+/// assert_eq!(build_url_search_params_btree(params), "B=x%20y&a=1&b=2");
+/// ```
+pub fn build_url_search_params_btree(params: BTreeMap<String, String>) -> String {
+    build_from_pairs(params)
 }
 
-pub struct Symbol {
-    pub new_line_carriage_return: &'static str,
-    pub new_line: &'static str,
-    pub carriage_return: &'static str,
-    pub empty_string: &'static str,
-    pub whitespace: &'static str,
-    pub equals: &'static str,
-    pub comma: &'static str,
-    pub hyphen: &'static str,
-    pub slash: &'static str,
-    pub semicolon: &'static str,
-    pub colon: &'static str,
-    pub number_sign: &'static str,
-    pub opening_square_bracket: &'static str,
-    pub closing_square_bracket: &'static str,
-    pub opening_curly_bracket: &'static str,
-    pub closing_curly_bracket: &'static str,
-    pub quotation_mark: &'static str,
-    pub underscore: &'static str,
-    pub single_quote: &'static str,
-    pub percent: &'static str,
-    pub exclamation_mark: &'static str,
-    pub dollar: &'static str,
-    pub ampersand: &'static str,
-    pub opening_bracket: &'static str,
-    pub closing_bracket: &'static str,
-    pub asterisk: &'static str,
-    pub plus: &'static str,
-    pub question_mark: &'static str,
-    pub at: &'static str,
+/// Convert given HashMap of keys with multiple values into a query string
+///
+/// Emits one pair per value, so `tag` with values `a` and `b` becomes
+/// `tag=a&tag=b`. Values keep their order, keys are sorted the same way
+/// [build_url_search_params] sorts them. Keys without values are omitted.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::build_url_search_params_multi;
+///
+/// let mut params_map: HashMap<String, Vec<String>> = HashMap::new();
+/// params_map.insert("tag".to_string(), vec!["b".to_string(), "a".to_string()]);
+/// params_map.insert("id".to_string(), vec!["1".to_string()]);
+///
+/// let search_params : String = build_url_search_params_multi(params_map);
+/// assert_eq!(search_params, "id=1&tag=b&tag=a");
+/// ```
+pub fn build_url_search_params_multi<S: BuildHasher>(params: HashMap<String, Vec<String>, S>) -> String {
+    let mut keys : Vec<(String, Vec<String>)> = params.into_iter().collect();
+    keys.sort_by(|(a, _), (b, _)| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
+
+    let mut url_search_params = String::new();
+    for (key, values) in keys {
+        for value in values {
+            if !url_search_params.is_empty() {
+                url_search_params.push('&');
+            }
+            encode_uri_component_into(&key, &mut url_search_params);
+            url_search_params.push('=');
+            encode_uri_component_into(&value, &mut url_search_params);
+        }
+    }
+
+    url_search_params
+}
+
+/// Convert given list of key-value pairs into a query string
+///
+/// Unlike [build_url_search_params] pairs are not sorted and are kept in
+/// the given order. Keys and values can be of any string-like type, such as
+/// `&str`, `String` or `Cow<str>`.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::build_url_search_params_ordered;
+///
+/// let params: Vec<(String, String)> = vec![
+///     ("b".to_string(), "1".to_string()),
+///     ("a".to_string(), "hello world".to_string()),
+/// ];
+///
+/// let search_params : String = build_url_search_params_ordered(&params);
+/// assert_eq!(search_params, "b=1&a=hello%20world");
+/// ```
+pub fn build_url_search_params_ordered<K: AsRef<str>, V: AsRef<str>>(params: &[(K, V)]) -> String {
+    let mut url_search_params = String::with_capacity(built_len(params));
+
+    for (index, (key, value)) in params.iter().enumerate() {
+        if index > 0 {
+            url_search_params.push('&');
+        }
+        encode_uri_component_into(key.as_ref(), &mut url_search_params);
+        url_search_params.push('=');
+        encode_uri_component_into(value.as_ref(), &mut url_search_params);
+    }
+
+    url_search_params
+}
+
+/// Convert given key-value pairs of any string-like types into a query string
+///
+/// Accepts anything iterable over pairs, such as `&[(&str, String)]`,
+/// `Vec<(&str, &str)>` or `HashMap::iter()`, so there is no need to clone
+/// everything into owned strings first. Pairs are kept in the iteration
+/// order, same as [build_url_search_params_ordered] does.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use url_search_params::build_from_pairs;
+///
+/// let search_params = build_from_pairs(vec![("q", "rust url".to_string()), ("page", "2".to_string())]);
+/// assert_eq!(search_params, "q=rust%20url&page=2");
+///
+/// let mut params: BTreeMap<&str, &str> = BTreeMap::new();
+/// params.insert("b", "2");
+/// params.insert("a", "1");
+/// assert_eq!(build_from_pairs(params.iter()), "a=1&b=2");
+/// ```
+pub fn build_from_pairs<K: AsRef<str>, V: AsRef<str>, I: IntoIterator<Item = (K, V)>>(pairs: I) -> String {
+    let mut url_search_params = String::new();
+
+    for (index, (key, value)) in pairs.into_iter().enumerate() {
+        if index > 0 {
+            url_search_params.push('&');
+        }
+        encode_uri_component_into(key.as_ref(), &mut url_search_params);
+        url_search_params.push('=');
+        encode_uri_component_into(value.as_ref(), &mut url_search_params);
+    }
+
+    url_search_params
+}
+
+/// Convert given key-value pairs of any `Display` types into a query string
+///
+/// Keys and values are formatted and then encoded, pairs are kept in the
+/// given order, same as [build_url_search_params_ordered] does.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::build_from_display;
+///
+/// let search_params = build_from_display([("page", 2), ("per_page", 20)]);
+/// assert_eq!(search_params, "page=2&per_page=20");
+/// ```
+pub fn build_from_display<K: fmt::Display, V: fmt::Display, I: IntoIterator<Item = (K, V)>>(pairs: I) -> String {
+    let mut url_search_params = String::new();
+
+    for (index, (key, value)) in pairs.into_iter().enumerate() {
+        if index > 0 {
+            url_search_params.push('&');
+        }
+        encode_uri_component_into(&key.to_string(), &mut url_search_params);
+        url_search_params.push('=');
+        encode_uri_component_into(&value.to_string(), &mut url_search_params);
+    }
+
+    url_search_params
+}
+
+/// Write query string built from the given list of key-value pairs into the writer
+///
+/// Output is the same as [build_url_search_params_ordered] returns, but it
+/// is written directly into the writer without building intermediate string.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+/// use url_search_params::write_url_search_params;
+///
+/// let params: Vec<(String, String)> = vec![
+///     ("b".to_string(), "1".to_string()),
+///     ("a".to_string(), "hello world".to_string()),
+/// ];
+///
+/// let mut url = String::from("https://example.com/?");
+/// write_url_search_params(&params, &mut url).unwrap();
+/// assert_eq!(url, "https://example.com/?b=1&a=hello%20world");
+/// ```
+pub fn write_url_search_params<K: AsRef<str>, V: AsRef<str>, W: fmt::Write>(pairs: &[(K, V)], out: &mut W) -> fmt::Result {
+    for (index, (key, value)) in pairs.iter().enumerate() {
+        if index > 0 {
+            out.write_char('&')?;
+        }
+        write_encoded(key.as_ref(), out)?;
+        out.write_char('=')?;
+        write_encoded(value.as_ref(), out)?;
+    }
+    Ok(())
+}
+
+/// Write given string encoded by [encode_uri_component] into the writer,
+/// passing through runs of bytes which do not need encoding as is
+fn write_encoded<W: fmt::Write>(component: &str, out: &mut W) -> fmt::Result {
+    let bytes = component.as_bytes();
+
+    let mut start = 0;
+    for (index, byte) in bytes.iter().enumerate() {
+        if ENCODE_TABLE[*byte as usize] {
+            if start < index {
+                out.write_str(&component[start..index])?;
+            }
+            out.write_char('%')?;
+            out.write_char(HEX_UPPER[(byte >> 4) as usize] as char)?;
+            out.write_char(HEX_UPPER[(byte & 0x0F) as usize] as char)?;
+            start = index + 1;
+        }
+    }
+    out.write_str(&component[start..])
+}
+
+/// Calculate length in bytes of the query string [build_url_search_params_ordered]
+/// would produce for the given pairs, without building it
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{build_url_search_params_ordered, built_len};
+///
+/// let params: Vec<(String, String)> = vec![
+///     ("b".to_string(), "1".to_string()),
+///     ("a".to_string(), "hello world".to_string()),
+/// ];
+///
+/// assert_eq!(built_len(&params), build_url_search_params_ordered(&params).len());
+/// ```
+pub fn built_len<K: AsRef<str>, V: AsRef<str>>(pairs: &[(K, V)]) -> usize {
+    let separators = pairs.len().saturating_sub(1);
+
+    pairs.iter()
+        .map(|(key, value)| encoded_len(key.as_ref()) + 1 + encoded_len(value.as_ref()))
+        .sum::<usize>() + separators
+}
+
+/// Length in bytes of the given string encoded by [encode_uri_component]
+fn encoded_len(component: &str) -> usize {
+    component.bytes()
+        .map(|byte| if ENCODE_TABLE[byte as usize] { 3 } else { 1 })
+        .sum()
+}
+
+/// Split given pairs into several query strings, each at most `max_bytes` long
+///
+/// Pairs are encoded like in [build_url_search_params_ordered] and packed
+/// greedily: a pair goes into the current query string while it fits,
+/// otherwise a new query string is started. Order of the pairs is kept.
+/// Returns [UrlSearchParamsError::LimitExceeded] if a single encoded pair
+/// is longer than `max_bytes`.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::chunk_params;
+///
+/// let params: Vec<(String, String)> = vec![
+///     ("id".to_string(), "1".to_string()),
+///     ("id".to_string(), "2".to_string()),
+///     ("id".to_string(), "3".to_string()),
+/// ];
+///
+/// assert_eq!(chunk_params(&params, 9).unwrap(), vec!["id=1&id=2", "id=3"]);
+/// assert!(chunk_params(&params, 3).is_err());
+/// ```
+pub fn chunk_params(pairs: &[(String, String)], max_bytes: usize) -> Result<Vec<String>, UrlSearchParamsError> {
+    let mut chunks : Vec<String> = vec![];
+    let mut chunk = String::new();
+
+    for (key, value) in pairs {
+        let pair = [encode_uri_component(key), "=".to_string(), encode_uri_component(value)].join("");
+        if pair.len() > max_bytes {
+            return Err(UrlSearchParamsError::LimitExceeded { limit: "pair bytes", max: max_bytes });
+        }
+
+        if !chunk.is_empty() && chunk.len() + 1 + pair.len() > max_bytes {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push('&');
+        }
+        chunk.push_str(&pair);
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
+
+/// Convert given list of key and optional value pairs into a query string
+///
+/// Keys without a value are emitted as is, without trailing `=`, which is
+/// the conventional way to write boolean flag parameters. Pairs are kept
+/// in the given order.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::build_url_search_params_optional;
+///
+/// let params: Vec<(String, Option<String>)> = vec![
+///     ("debug".to_string(), None),
+///     ("a".to_string(), Some("1".to_string())),
+/// ];
+///
+/// let search_params : String = build_url_search_params_optional(&params);
+/// assert_eq!(search_params, "debug&a=1");
+/// ```
+pub fn build_url_search_params_optional(params: &[(String, Option<String>)]) -> String {
+
+    let mut key_value_list : Vec<String> = vec![];
+    for (key, value) in params {
+        let param = match value {
+            Some(value) => [encode_uri_component(key.as_str()), "=".to_string(), encode_uri_component(value.as_str())].join(""),
+            None => encode_uri_component(key.as_str()),
+        };
+        key_value_list.push(param);
+    }
+
+    key_value_list.join("&")
+}
+
+/// Append given query string to the URL
+///
+/// Uses `?` as a joiner if URL has no query string yet and `&` otherwise.
+/// Fragment part of the URL, if any, is moved after the query string, as
+/// fragment is not part of the query string.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::append_to_url;
+///
+/// let url = append_to_url("https://example.com/path#top", "key=value");
+/// assert_eq!(url, "https://example.com/path?key=value#top");
+///
+/// let url = append_to_url("https://example.com/path?a=1", "key=value");
+/// assert_eq!(url, "https://example.com/path?a=1&key=value");
+/// ```
+pub fn append_to_url(url: &str, query: &str) -> String {
+    let (base, fragment) = match url.find('#') {
+        Some(index) => (&url[..index], &url[index..]),
+        None => (url, SYMBOL.empty_string),
+    };
+
+    if query.is_empty() {
+        return url.to_string()
+    }
+
+    let joiner = match base.find('?') {
+        None => SYMBOL.question_mark,
+        Some(_) if base.ends_with('?') || base.ends_with('&') => SYMBOL.empty_string,
+        Some(_) => SYMBOL.ampersand,
+    };
+
+    [base, joiner, query, fragment].join("")
+}
+
+/// Split the part of URL after the path into query string and fragment
+///
+/// Leading question mark `?` and hash mark `#` delimiters are not part of
+/// the returned query string and fragment. Query string is empty if there
+/// is none, fragment is `None` if there is no hash mark.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_url_search_params, split_query_fragment};
+///
+/// let (query, fragment) = split_query_fragment("?a=1#section");
+/// assert_eq!(query, "a=1");
+/// assert_eq!(fragment, Some("section"));
+///
+/// let params = parse_url_search_params(query);
+/// assert_eq!(params.get("a"), Some(&"1".to_string()));
+/// ```
+pub fn split_query_fragment(tail: &str) -> (&str, Option<&str>) {
+    let (query, fragment) = match tail.find('#') {
+        Some(index) => (&tail[..index], Some(&tail[index + 1..])),
+        None => (tail, None),
+    };
+
+    (query.strip_prefix('?').unwrap_or(query), fragment)
+}
+
+/// Parse query string of the given URL, returning the part before the query
+/// string, parsed parameters and the fragment
+///
+/// As per RFC 3986 query string starts after the first `?` and ends at the
+/// first `#`, a `?` inside the fragment does not start a query string.
+/// Returned part before the query string includes scheme, authority and
+/// path, fragment is `None` if there is no hash mark. Parameters are parsed
+/// via [parse_url_search_params].
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::parse_from_url;
+///
+/// let (path, params, fragment) = parse_from_url("https://example.com/search?q=rust%20url#results?page=2");
+///
+/// assert_eq!(path, "https://example.com/search");
+/// assert_eq!(params.get("q"), Some(&"rust url".to_string()));
+/// assert_eq!(params.len(), 1);
+/// assert_eq!(fragment, Some("results?page=2"));
+/// ```
+pub fn parse_from_url(url: &str) -> (&str, HashMap<String, String>, Option<&str>) {
+    let path_end = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, tail) = url.split_at(path_end);
+    let (query, fragment) = split_query_fragment(tail);
+
+    (path, parse_url_search_params(query), fragment)
+}
+
+/// Convert parameters held in the URL fragment into a HashMap
+///
+/// One leading hash mark `#` is stripped. Fragment parameters, such as ones
+/// of OAuth 2.0 implicit flow, are `application/x-www-form-urlencoded`, so
+/// plus sign `+` is decoded as whitespace, same as [parse_form] does.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_fragment_params, parse_from_url};
+///
+/// let (_, _, fragment) = parse_from_url("https://example.com/cb#access_token=a%2Bb&token_type=Bearer&state=x+y");
+/// let params = parse_fragment_params(fragment.unwrap_or(""));
+///
+/// assert_eq!(params.get("access_token"), Some(&"a+b".to_string()));
+/// assert_eq!(params.get("state"), Some(&"x y".to_string()));
+/// ```
+pub fn parse_fragment_params(fragment: &str) -> HashMap<String, String> {
+    parse_form(fragment.strip_prefix('#').unwrap_or(fragment))
+}
+
+/// Lookup table for the default encode set [EncodeSet::COMPONENT] used by
+/// [encode_uri_component], indexed by byte value, `true` means the byte has to be percent-encoded
+///
+/// Only RFC 3986 unreserved characters `A-Z a-z 0-9 - . _ ~` and question
+/// mark `?` are left as is, every other byte, including control characters
+/// and bytes of non-ASCII characters, is encoded.
+pub static ENCODE_TABLE: [bool; 256] = build_encode_table();
+
+const fn build_encode_table() -> [bool; 256] {
+    let mut table = [false; 256];
+    let mut index = 0;
+    while index < table.len() {
+        table[index] = needs_encoding(index as u8);
+        index += 1;
+    }
+    table
+}
+
+/// Check if given byte has to be percent-encoded by [encode_uri_component]
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::needs_encoding;
+///
+/// assert!(needs_encoding(b' '));
+/// assert!(needs_encoding(b'&'));
+/// assert!(!needs_encoding(b'a'));
+/// assert!(!needs_encoding(b'~'));
+/// ```
+pub const fn needs_encoding(byte: u8) -> bool {
+    !matches!(byte, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'?')
+}
+
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+
+/// Percent-encode given string so it can be safely used as a query string key or value
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component;
+///
+/// assert_eq!(encode_uri_component("a b&c=d"), "a%20b%26c%3Dd");
+/// assert_eq!(encode_uri_component("ü"), "%C3%BC");
+/// ```
+pub fn encode_uri_component(component: &str) -> String {
+    let mut result = String::with_capacity(component.len());
+    encode_uri_component_into(component, &mut result);
+    result
+}
+
+/// Percent-encode given string appending result to the given buffer
+///
+/// Same as [encode_uri_component], but lets caller reuse the buffer
+/// between calls instead of allocating new string each time.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component_into;
+///
+/// let mut buffer = String::new();
+/// encode_uri_component_into("a b", &mut buffer);
+/// assert_eq!(buffer, "a%20b");
+///
+/// buffer.clear();
+/// encode_uri_component_into("c&d", &mut buffer);
+/// assert_eq!(buffer, "c%26d");
+/// ```
+pub fn encode_uri_component_into(component: &str, out: &mut String) {
+    for byte in component.bytes() {
+        encode_byte_into(byte, &ENCODE_TABLE, HEX_UPPER, out);
+    }
+}
+
+/// Percent-encode given string, same as [encode_uri_component] does, but
+/// whitespace is encoded as `+`
+///
+/// Equivalent to [encode_uri_component_with] with [EncodeSet::FORM].
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{encode_uri_component, encode_uri_component_space_as_plus};
+///
+/// assert_eq!(encode_uri_component("a b"), "a%20b");
+/// assert_eq!(encode_uri_component_space_as_plus("a b"), "a+b");
+/// ```
+pub fn encode_uri_component_space_as_plus(component: &str) -> String {
+    encode_uri_component_with(component, &EncodeSet::FORM)
+}
+
+/// Percent-encode given string, reporting whether any byte was actually encoded
+///
+/// Returned string is the same as [encode_uri_component] returns.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component_checked;
+///
+/// assert_eq!(encode_uri_component_checked("abc123"), ("abc123".to_string(), false));
+/// assert_eq!(encode_uri_component_checked("a b"), ("a%20b".to_string(), true));
+/// ```
+pub fn encode_uri_component_checked(component: &str) -> (String, bool) {
+    let is_encoded = component.bytes().any(|byte| ENCODE_TABLE[byte as usize]);
+    if !is_encoded {
+        return (component.to_string(), false)
+    }
+
+    (encode_uri_component(component), true)
+}
+
+/// Percent-encode given string using uppercase or lowercase hex digits
+///
+/// [encode_uri_component] always uses uppercase hex digits as recommended by
+/// RFC 3986, this function allows to emit lowercase ones for endpoints
+/// requiring them.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component_with_hex_case;
+///
+/// assert_eq!(encode_uri_component_with_hex_case("a=b", true), "a%3Db");
+/// assert_eq!(encode_uri_component_with_hex_case("a=b", false), "a%3db");
+/// ```
+pub fn encode_uri_component_with_hex_case(component: &str, upper: bool) -> String {
+    let hex = if upper { HEX_UPPER } else { HEX_LOWER };
+
+    let mut result = String::with_capacity(component.len());
+    for byte in component.bytes() {
+        encode_byte_into(byte, &ENCODE_TABLE, hex, &mut result);
+    }
+    result
+}
+
+fn encode_byte_into(byte: u8, table: &[bool; 256], hex: &[u8; 16], out: &mut String) {
+    if table[byte as usize] {
+        out.push('%');
+        out.push(hex[(byte >> 4) as usize] as char);
+        out.push(hex[(byte & 0x0F) as usize] as char);
+    } else {
+        out.push(byte as char);
+    }
+}
+
+/// Lookup table for the path segment encode set used by [encode_path_segment]
+static PATH_SEGMENT_ENCODE_TABLE: [bool; 256] = build_path_segment_encode_table();
+
+const fn build_path_segment_encode_table() -> [bool; 256] {
+    let mut table = [false; 256];
+    let mut index = 0;
+    while index < table.len() {
+        let byte = index as u8;
+        table[index] = !matches!(byte,
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~'
+            | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'='
+            | b':' | b'@');
+        index += 1;
+    }
+    table
+}
+
+/// Percent-encode given string so it can be safely used as a single URL path segment
+///
+/// Unlike [encode_uri_component], which is meant for query string keys and
+/// values, it keeps every RFC 3986 `pchar` character as is: unreserved
+/// characters `A-Z a-z 0-9 - . _ ~`, sub-delimiters `! $ & ' ( ) * + , ; =`,
+/// colon `:` and at sign `@`. Slash `/` and question mark `?` are encoded, as
+/// they would end the path segment, same as percent sign, whitespace and any
+/// other byte.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_path_segment;
+///
+/// assert_eq!(encode_path_segment("a b/c:d"), "a%20b%2Fc:d");
+/// ```
+pub fn encode_path_segment(segment: &str) -> String {
+    let mut result = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        encode_byte_into(byte, &PATH_SEGMENT_ENCODE_TABLE, HEX_UPPER, &mut result);
+    }
+    result
 }
 
-pub const SYMBOL: Symbol = Symbol {
-    new_line: "\n",
-    carriage_return: "\r",
-    new_line_carriage_return: "\r\n",
-    empty_string: "",
-    whitespace: " ",
-    equals: "=",
-    comma: ",",
-    hyphen: "-",
-    slash: "/",
-    semicolon: ";",
-    colon: ":",
-    number_sign: "#",
-    opening_square_bracket: "[",
-    closing_square_bracket: "]",
-    opening_curly_bracket: "{",
-    closing_curly_bracket: "}",
-    quotation_mark: "\"",
-    underscore: "_",
-    single_quote: "'",
-    percent: "%",
-    exclamation_mark: "!",
-    dollar: "$",
-    ampersand: "&",
-    opening_bracket: "(",
-    closing_bracket: ")",
-    asterisk: "*",
-    plus: "+",
-    question_mark: "?",
-    at: "@",
-};
+/// Lookup table for the fragment encode set used by [encode_fragment]
+static FRAGMENT_ENCODE_TABLE: [bool; 256] = build_fragment_encode_table();
+
+const fn build_fragment_encode_table() -> [bool; 256] {
+    let mut table = build_path_segment_encode_table();
+    table[b'/' as usize] = false;
+    table[b'?' as usize] = false;
+    table
+}
+
+/// Percent-encode given string so it can be safely used as a URL fragment
+///
+/// Fragment is not sent to a server, but client-side routers still parse
+/// it. Unlike [encode_uri_component], it keeps every character RFC 3986
+/// permits in a fragment as is: the `pchar` set kept by [encode_path_segment]
+/// plus slash `/` and question mark `?`. Hash mark `#`, percent sign,
+/// whitespace and any other byte are encoded.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_fragment;
+///
+/// assert_eq!(encode_fragment("/users/1?tab=posts and more"), "/users/1?tab=posts%20and%20more");
+/// ```
+pub fn encode_fragment(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        encode_byte_into(byte, &FRAGMENT_ENCODE_TABLE, HEX_UPPER, &mut result);
+    }
+    result
+}
+
+/// Percent-encode given string leaving already present escapes untouched
+///
+/// Works as [encode_uri_component], except `%` followed by two hex digits
+/// is considered a valid escape and is copied as is, so already encoded
+/// parts of the string are not encoded twice. Keep in mind it is ambiguous:
+/// literal `%` followed by two hex digits, like in `100%AB`, is assumed to
+/// be encoded already. Lone `%` is encoded to `%25`.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::encode_uri_component_idempotent;
+///
+/// assert_eq!(encode_uri_component_idempotent("a%20b c"), "a%20b%20c");
+/// assert_eq!(encode_uri_component_idempotent("100%"), "100%25");
+/// ```
+pub fn encode_uri_component_idempotent(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut result = String::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        if is_percent_escape(bytes, index) {
+            result.push_str(&component[index..index + 3]);
+            index += 3;
+            continue;
+        }
+        encode_byte_into(bytes[index], &ENCODE_TABLE, HEX_UPPER, &mut result);
+        index += 1;
+    }
+
+    result
+}
+
+fn is_percent_escape(bytes: &[u8], index: usize) -> bool {
+    bytes[index] == b'%'
+        && index + 2 < bytes.len()
+        && hex_value(bytes[index + 1]).is_some()
+        && hex_value(bytes[index + 2]).is_some()
+}
+
+/// Decode percent-encoded string
+///
+/// Every valid `%XX` escape is decoded into a byte, malformed escapes are
+/// kept as is. Decoded bytes which do not form valid UTF-8 are replaced with
+/// U+FFFD replacement character.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::decode_uri_component;
+///
+/// assert_eq!(decode_uri_component("a%20b%26c%3Dd"), "a b&c=d");
+/// assert_eq!(decode_uri_component("%C3%BC"), "ü");
+/// assert_eq!(decode_uri_component("100%"), "100%");
+/// ```
+pub fn decode_uri_component(component: &str) -> String {
+    let mut result = String::with_capacity(component.len());
+    decode_uri_component_into(component, &mut result);
+    result
+}
+
+/// Decode percent-encoded string appending result to the given buffer
+///
+/// Same as [decode_uri_component], but lets caller reuse the buffer
+/// between calls instead of allocating new string each time.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::decode_uri_component_into;
+///
+/// let mut buffer = String::new();
+/// decode_uri_component_into("a%20b", &mut buffer);
+/// assert_eq!(buffer, "a b");
+///
+/// buffer.clear();
+/// decode_uri_component_into("c%26d", &mut buffer);
+/// assert_eq!(buffer, "c&d");
+/// ```
+pub fn decode_uri_component_into(component: &str, out: &mut String) {
+    let mut buffer = std::mem::take(out).into_bytes();
+    let start = buffer.len();
+    percent_decode_into(component.as_bytes(), &mut buffer);
+
+    *out = match String::from_utf8(buffer) {
+        Ok(decoded) => decoded,
+        Err(error) => {
+            let mut bytes = error.into_bytes();
+            let decoded = String::from_utf8_lossy(&bytes[start..]).into_owned();
+            bytes.truncate(start);
+            let mut result = String::from_utf8_lossy(&bytes).into_owned();
+            result.push_str(&decoded);
+            result
+        }
+    };
+}
+
+/// Decode given input up to the first delimiter, returning decoded string
+/// and the number of bytes consumed
+///
+/// Delimiters are literal ampersand `&` and equals sign `=`, the delimiter
+/// itself is not consumed, so `input[consumed..]` starts with it, if any.
+/// Encoded delimiters, such as `%26` and `%3D`, are decoded as part of the
+/// string and do not stop decoding. Decoding rules are the same as for
+/// [decode_uri_component].
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::decode_one;
+///
+/// let input = "a%26b=c&d";
+///
+/// let (key, consumed) = decode_one(input);
+/// assert_eq!(key, "a&b");
+/// assert_eq!(consumed, 5);
+///
+/// let (value, consumed) = decode_one(&input[consumed + 1..]);
+/// assert_eq!(value, "c");
+/// assert_eq!(consumed, 1);
+/// ```
+pub fn decode_one(input: &str) -> (String, usize) {
+    let consumed = input.find(['&', '=']).unwrap_or(input.len());
+    (decode_uri_component(&input[..consumed]), consumed)
+}
+
+/// Decode percent-encoded string, failing on malformed input
+///
+/// Unlike [decode_uri_component], which keeps malformed escapes as is and
+/// replaces invalid UTF-8, returns [UrlSearchParamsError::Decode] for `%`
+/// not followed by two hex digits and [UrlSearchParamsError::InvalidUtf8]
+/// if decoded bytes are not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{try_decode_uri_component, UrlSearchParamsError};
+///
+/// assert_eq!(try_decode_uri_component("a%20b"), Ok("a b".to_string()));
+///
+/// let error = UrlSearchParamsError::Decode { input: "100%".to_string(), index: 3 };
+/// assert_eq!(try_decode_uri_component("100%"), Err(error));
+/// ```
+pub fn try_decode_uri_component(component: &str) -> Result<String, UrlSearchParamsError> {
+    if let Some(index) = find_malformed_escape(component) {
+        return Err(UrlSearchParamsError::Decode { input: component.to_string(), index })
+    }
+
+    let bytes = component.as_bytes();
+    let mut decoded : Vec<u8> = Vec::with_capacity(bytes.len());
+    percent_decode_into(bytes, &mut decoded);
+
+    String::from_utf8(decoded)
+        .map_err(|_| UrlSearchParamsError::InvalidUtf8(component.to_string()))
+}
+
+/// Decode percent-encoded string, handling malformed input according to
+/// the mode
+///
+/// [DecodeMode::Strict] works as [try_decode_uri_component],
+/// [DecodeMode::Lossy] as [decode_uri_component] and never fails.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{decode_uri_component_with, DecodeMode};
+///
+/// assert_eq!(decode_uri_component_with("100%25", DecodeMode::Strict), Ok("100%".to_string()));
+/// assert_eq!(decode_uri_component_with("%ZZ%", DecodeMode::Lossy), Ok("%ZZ%".to_string()));
+/// assert!(decode_uri_component_with("%ZZ%", DecodeMode::Strict).is_err());
+/// ```
+pub fn decode_uri_component_with(component: &str, mode: DecodeMode) -> Result<String, UrlSearchParamsError> {
+    match mode {
+        DecodeMode::Strict => try_decode_uri_component(component),
+        DecodeMode::Lossy => Ok(decode_uri_component(component)),
+    }
+}
+
+/// Decode percent-encoded string, handling decoded bytes which are not
+/// valid UTF-8 according to the policy
+///
+/// Malformed escapes are kept as is, same as in [decode_uri_component].
+/// Only [Utf8Policy::Error] can fail.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{decode_uri_component_utf8, Utf8Policy, UrlSearchParamsError};
+///
+/// assert_eq!(decode_uri_component_utf8("a%FFb", Utf8Policy::Replace), Ok("a\u{FFFD}b".to_string()));
+/// assert_eq!(decode_uri_component_utf8("a%FFb%C3%BC", Utf8Policy::KeepEscaped), Ok("a%FFbü".to_string()));
+/// assert_eq!(decode_uri_component_utf8("a%FFb", Utf8Policy::Error), Err(UrlSearchParamsError::InvalidUtf8("a%FFb".to_string())));
+/// ```
+pub fn decode_uri_component_utf8(component: &str, policy: Utf8Policy) -> Result<String, UrlSearchParamsError> {
+    let mut decoded : Vec<u8> = Vec::with_capacity(component.len());
+    percent_decode_into(component.as_bytes(), &mut decoded);
+
+    let error = match String::from_utf8(decoded) {
+        Ok(decoded) => return Ok(decoded),
+        Err(error) => error,
+    };
+
+    match policy {
+        Utf8Policy::Replace => Ok(String::from_utf8_lossy(error.as_bytes()).into_owned()),
+        Utf8Policy::KeepEscaped => Ok(escape_invalid_utf8(error.as_bytes())),
+        Utf8Policy::Error => Err(UrlSearchParamsError::InvalidUtf8(component.to_string())),
+    }
+}
+
+/// Convert given bytes into a string, percent-encoding every byte of
+/// invalid UTF-8 sequences
+fn escape_invalid_utf8(mut bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                result.push_str(valid);
+                return result
+            }
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                result.push_str(std::str::from_utf8(valid).unwrap_or_default());
+
+                let invalid_len = error.error_len().unwrap_or(rest.len());
+                for byte in &rest[..invalid_len] {
+                    result.push('%');
+                    result.push(HEX_UPPER[(byte >> 4) as usize] as char);
+                    result.push(HEX_UPPER[(byte & 0x0F) as usize] as char);
+                }
+                bytes = &rest[invalid_len..];
+            }
+        }
+    }
+}
+
+/// Find byte index of the first `%` not followed by two hex digits
+pub(crate) fn find_malformed_escape(component: &str) -> Option<usize> {
+    let bytes = component.as_bytes();
+    (0..bytes.len()).find(|index| bytes[*index] == b'%' && !is_percent_escape(bytes, *index))
+}
+
+fn percent_decode_into(bytes: &[u8], out: &mut Vec<u8>) {
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if byte == b'%' && index + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex_value(bytes[index + 1]), hex_value(bytes[index + 2])) {
+                out.push(high << 4 | low);
+                index += 3;
+                continue;
+            }
+        }
+        out.push(byte);
+        index += 1;
+    }
+}
+
+const fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        _ => None,
+    }
+}
+
+pub struct Symbol {
+    pub new_line_carriage_return: &'static str,
+    pub new_line: &'static str,
+    pub carriage_return: &'static str,
+    pub empty_string: &'static str,
+    pub whitespace: &'static str,
+    pub equals: &'static str,
+    pub comma: &'static str,
+    pub hyphen: &'static str,
+    pub slash: &'static str,
+    pub semicolon: &'static str,
+    pub colon: &'static str,
+    pub number_sign: &'static str,
+    pub opening_square_bracket: &'static str,
+    pub closing_square_bracket: &'static str,
+    pub opening_curly_bracket: &'static str,
+    pub closing_curly_bracket: &'static str,
+    pub quotation_mark: &'static str,
+    pub underscore: &'static str,
+    pub single_quote: &'static str,
+    pub percent: &'static str,
+    pub exclamation_mark: &'static str,
+    pub dollar: &'static str,
+    pub ampersand: &'static str,
+    pub opening_bracket: &'static str,
+    pub closing_bracket: &'static str,
+    pub asterisk: &'static str,
+    pub plus: &'static str,
+    pub question_mark: &'static str,
+    pub at: &'static str,
+    pub backtick: &'static str,
+    pub vertical_bar: &'static str,
+    pub tilde: &'static str,
+    pub caret: &'static str,
+    pub less_than: &'static str,
+    pub greater_than: &'static str,
+}
+
+impl Symbol {
+    /// List of single character symbols with their percent-encoded form
+    ///
+    /// Covers every symbol of [SYMBOL] except the empty string and `\r\n`.
+    /// Some of the symbols, like `-` or `~`, are left as is by
+    /// [encode_uri_component], their percent-encoded form is still valid
+    /// and decodes back to the symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::{decode_uri_component, Symbol};
+    ///
+    /// for (symbol, encoded) in Symbol::all() {
+    ///     assert_eq!(decode_uri_component(encoded), *symbol);
+    /// }
+    /// ```
+    pub const fn all() -> &'static [(&'static str, &'static str)] {
+        &SYMBOL_ENCODING
+    }
+}
+
+const SYMBOL_ENCODING: [(&str, &str); 33] = [
+    (SYMBOL.new_line, "%0A"),
+    (SYMBOL.carriage_return, "%0D"),
+    (SYMBOL.whitespace, "%20"),
+    (SYMBOL.exclamation_mark, "%21"),
+    (SYMBOL.quotation_mark, "%22"),
+    (SYMBOL.number_sign, "%23"),
+    (SYMBOL.dollar, "%24"),
+    (SYMBOL.percent, "%25"),
+    (SYMBOL.ampersand, "%26"),
+    (SYMBOL.single_quote, "%27"),
+    (SYMBOL.opening_bracket, "%28"),
+    (SYMBOL.closing_bracket, "%29"),
+    (SYMBOL.asterisk, "%2A"),
+    (SYMBOL.plus, "%2B"),
+    (SYMBOL.comma, "%2C"),
+    (SYMBOL.hyphen, "%2D"),
+    (SYMBOL.slash, "%2F"),
+    (SYMBOL.colon, "%3A"),
+    (SYMBOL.semicolon, "%3B"),
+    (SYMBOL.less_than, "%3C"),
+    (SYMBOL.equals, "%3D"),
+    (SYMBOL.greater_than, "%3E"),
+    (SYMBOL.question_mark, "%3F"),
+    (SYMBOL.at, "%40"),
+    (SYMBOL.opening_square_bracket, "%5B"),
+    (SYMBOL.closing_square_bracket, "%5D"),
+    (SYMBOL.caret, "%5E"),
+    (SYMBOL.underscore, "%5F"),
+    (SYMBOL.backtick, "%60"),
+    (SYMBOL.opening_curly_bracket, "%7B"),
+    (SYMBOL.vertical_bar, "%7C"),
+    (SYMBOL.closing_curly_bracket, "%7D"),
+    (SYMBOL.tilde, "%7E"),
+];
+
+pub const SYMBOL: Symbol = Symbol {
+    new_line: "\n",
+    carriage_return: "\r",
+    new_line_carriage_return: "\r\n",
+    empty_string: "",
+    whitespace: " ",
+    equals: "=",
+    comma: ",",
+    hyphen: "-",
+    slash: "/",
+    semicolon: ";",
+    colon: ":",
+    number_sign: "#",
+    opening_square_bracket: "[",
+    closing_square_bracket: "]",
+    opening_curly_bracket: "{",
+    closing_curly_bracket: "}",
+    quotation_mark: "\"",
+    underscore: "_",
+    single_quote: "'",
+    percent: "%",
+    exclamation_mark: "!",
+    dollar: "$",
+    ampersand: "&",
+    opening_bracket: "(",
+    closing_bracket: ")",
+    asterisk: "*",
+    plus: "+",
+    question_mark: "?",
+    at: "@",
+    backtick: "`",
+    vertical_bar: "|",
+    tilde: "~",
+    caret: "^",
+    less_than: "<",
+    greater_than: ">",
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use crate::{any, append_to_url, build_from_display, build_from_pairs, build_url_search_params, build_url_search_params_btree, build_url_search_params_by, build_url_search_params_multi, build_url_search_params_optional, build_url_search_params_ordered, built_len, chunk_params, decode_one, decode_uri_component, decode_uri_component_into, decode_uri_component_utf8, decode_uri_component_with, encode_fragment, encode_path_segment, encode_uri_component, encode_uri_component_checked, encode_uri_component_idempotent, encode_uri_component_into, encode_uri_component_space_as_plus, encode_uri_component_with_hex_case, first_raw, flag_set, key_set, needs_encoding, nth_param, parse_fragment_params, parse_from_url, parse_grouped_ordered, parse_into, parse_matrix_params, parse_url_search_params, parse_url_search_params_btree, parse_url_search_params_multi, parse_url_search_params_optional, parse_url_search_params_ordered, parse_with_decoder, split_query_fragment, take_params, try_decode_uri_component, try_parse_url_search_params, values_for, write_url_search_params, DecodeMode, ENCODE_TABLE, ParseError, ParseOptions, SYMBOL, Symbol, UrlSearchParamsError, Utf8Policy};
+
+    #[test]
+    fn build_url_search_params_test() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("key1".to_string(), "test1".to_string());
+        params_map.insert("key2".to_string(), "test2".to_string());
+        params_map.insert("".to_string(), "empty_key".to_string());
+        params_map.insert("empty_value".to_string(), "".to_string());
+
+        let search_params = build_url_search_params(params_map);
+        let parsed_search_params = parse_url_search_params(&search_params);
+
+        let boxed_get = parsed_search_params.get("key1");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "test1");
+
+        let boxed_get = parsed_search_params.get("key2");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "test2");
+
+        let boxed_get = parsed_search_params.get("");
+        assert!(boxed_get.is_none());
+
+        let boxed_get = parsed_search_params.get("empty_value");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "");
+    }
+
+    #[test]
+    fn build_url_search_params_ampersand() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("key1".to_string(), "test1".to_string());
+        params_map.insert("key2".to_string(), "test2".to_string());
+        params_map.insert("".to_string(), "empty_key".to_string());
+        params_map.insert("empty_value".to_string(), "".to_string());
+        params_map.insert("&".to_string(), "unescaped_ampersand_as_key".to_string());
+
+        let search_params = build_url_search_params(params_map);
+        let parsed_search_params = parse_url_search_params(&search_params);
+
+        let boxed_get = parsed_search_params.get("key1");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "test1");
+
+        let boxed_get = parsed_search_params.get("key2");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "test2");
+
+        let boxed_get = parsed_search_params.get("");
+        assert!(boxed_get.is_none());
+
+
+        let boxed_get = parsed_search_params.get("empty_value");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "");
+
+        let boxed_get = parsed_search_params.get("empty_value");
+        assert!(boxed_get.is_some());
+
+    }
+
+    #[test]
+    fn parse_empty_url_search_params() {
+        let search_params = "";
+        let params = parse_url_search_params(search_params);
+        assert_eq!(0, params.len());
+    }
+
+    #[test]
+    fn parse_empty_equals_ampersand_search_params() {
+        let search_params = "=&key2=value2";
+        let params = parse_url_search_params(search_params);
+        assert_eq!(1, params.len());
+
+        let boxed_get = params.get("key2");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "value2");
+    }
+
+    #[test]
+    fn encode_decode() {
+        let component = "\r\n \"%!#$&'()*+,/:;=?@[]][@?=;:/,+*)('&$#!%\" \r\n";
+        let mut _result = encode_uri_component(component);
+        assert_eq!("%0D%0A%20%22%25%21%23%24%26%27%28%29%2A%2B%2C%2F%3A%3B%3D?%40%5B%5D%5D%5B%40?%3D%3B%3A%2F%2C%2B%2A%29%28%27%26%24%23%21%25%22%20%0D%0A", _result);
+        _result = decode_uri_component(_result.as_str());
+        assert_eq!(component, _result);
+    }
+
+    #[test]
+    fn build_url_search_params_optional_test() {
+        let params: Vec<(String, Option<String>)> = vec![
+            ("debug".to_string(), None),
+            ("a".to_string(), Some("1".to_string())),
+            ("empty".to_string(), Some("".to_string())),
+            ("flag&".to_string(), None),
+        ];
+
+        let search_params = build_url_search_params_optional(&params);
+        assert_eq!("debug&a=1&empty=&flag%26", search_params);
+
+        let parsed_search_params = parse_url_search_params(&search_params);
+
+        let boxed_get = parsed_search_params.get("debug");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "");
+
+        let boxed_get = parsed_search_params.get("flag&");
+        assert!(boxed_get.is_some());
+    }
+
+    #[test]
+    fn build_url_search_params_optional_empty() {
+        let params: Vec<(String, Option<String>)> = vec![];
+        let search_params = build_url_search_params_optional(&params);
+        assert_eq!("", search_params);
+    }
+
+    #[test]
+    fn append_to_url_without_query() {
+        let url = append_to_url("https://example.com/path", "key=value");
+        assert_eq!("https://example.com/path?key=value", url);
+    }
+
+    #[test]
+    fn append_to_url_with_query() {
+        let url = append_to_url("https://example.com/path?a=1", "key=value");
+        assert_eq!("https://example.com/path?a=1&key=value", url);
+
+        let url = append_to_url("https://example.com/path?", "key=value");
+        assert_eq!("https://example.com/path?key=value", url);
+
+        let url = append_to_url("https://example.com/path?a=1&", "key=value");
+        assert_eq!("https://example.com/path?a=1&key=value", url);
+    }
+
+    #[test]
+    fn append_to_url_with_fragment() {
+        let url = append_to_url("https://example.com/path#section", "key=value");
+        assert_eq!("https://example.com/path?key=value#section", url);
+
+        let url = append_to_url("https://example.com/path?a=1#section?b=2", "key=value");
+        assert_eq!("https://example.com/path?a=1&key=value#section?b=2", url);
+    }
+
+    #[test]
+    fn append_to_url_empty_query() {
+        let url = append_to_url("https://example.com/path#section", "");
+        assert_eq!("https://example.com/path#section", url);
+    }
+
+    #[test]
+    fn encode_table_matches_needs_encoding() {
+        for byte in 0..=255u8 {
+            assert_eq!(needs_encoding(byte), ENCODE_TABLE[byte as usize]);
+        }
+
+        for byte in 0..0x80u8 {
+            let component = char::from(byte).to_string();
+            assert_eq!(needs_encoding(byte), encode_uri_component(&component) != component);
+        }
+
+        assert!(needs_encoding(0x00));
+        assert!(needs_encoding(0x7F));
+        assert!(needs_encoding(0x80));
+        assert!(needs_encoding(0xFF));
+        assert!(needs_encoding(b'%'));
+        assert!(!needs_encoding(b'Z'));
+        assert!(!needs_encoding(b'9'));
+        assert!(!needs_encoding(b'?'));
+    }
+
+    #[test]
+    fn encode_decode_non_ascii() {
+        let component = "привіт мир | <tag> {x} ^ `";
+        let _result = encode_uri_component(component);
+        assert!(_result.bytes().all(|byte| byte == b'%' || !needs_encoding(byte)));
+        assert_eq!(component, decode_uri_component(_result.as_str()));
+    }
+
+    #[test]
+    fn decode_malformed_escape() {
+        assert_eq!("%", decode_uri_component("%"));
+        assert_eq!("%2", decode_uri_component("%2"));
+        assert_eq!("%ZZ", decode_uri_component("%ZZ"));
+        assert_eq!("%%20", decode_uri_component("%%2520"));
+        assert_eq!("\u{FFFD}", decode_uri_component("%FF"));
+    }
+
+    #[test]
+    fn parse_encoded_equals_in_key() {
+        let search_params = "a%3Db=1";
+        let params = parse_url_search_params(search_params);
+        assert_eq!(1, params.len());
+
+        let boxed_get = params.get("a=b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "1");
+
+        assert!(!params.contains_key("a"));
+    }
+
+    #[test]
+    fn build_parse_equals_in_key() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("a=b".to_string(), "c=d".to_string());
+
+        let search_params = build_url_search_params(params_map);
+        assert_eq!("a%3Db=c%3Dd", search_params);
+
+        let parsed_search_params = parse_url_search_params(&search_params);
+        assert_eq!(1, parsed_search_params.len());
+
+        let boxed_get = parsed_search_params.get("a=b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "c=d");
+    }
+
+    #[test]
+    fn encode_decode_into_reused_buffer() {
+        let mut buffer = String::with_capacity(64);
+        let capacity = buffer.capacity();
+
+        encode_uri_component_into("key=value", &mut buffer);
+        assert_eq!("key%3Dvalue", buffer);
+
+        encode_uri_component_into(" & ", &mut buffer);
+        assert_eq!("key%3Dvalue%20%26%20", buffer);
+
+        buffer.clear();
+        decode_uri_component_into("key%3Dvalue", &mut buffer);
+        assert_eq!("key=value", buffer);
+
+        decode_uri_component_into("%20%26%20", &mut buffer);
+        assert_eq!("key=value & ", buffer);
+        assert_eq!(capacity, buffer.capacity());
+    }
+
+    #[test]
+    fn decode_into_invalid_utf8_keeps_prefix() {
+        let mut buffer = String::from("ü");
+        decode_uri_component_into("%FFa", &mut buffer);
+        assert_eq!("ü\u{FFFD}a", buffer);
+    }
+
+    #[test]
+    fn build_url_search_params_deterministic() {
+        let mut expected = String::new();
+        for _ in 0..100 {
+            let mut params_map: HashMap<String, String> = HashMap::new();
+            params_map.insert("key".to_string(), "value".to_string());
+            params_map.insert("Key".to_string(), "VALUE".to_string());
+            params_map.insert("KEY".to_string(), "Value".to_string());
+            params_map.insert("kEy".to_string(), "value".to_string());
+            params_map.insert("a".to_string(), "1".to_string());
+
+            let search_params = build_url_search_params(params_map);
+            if expected.is_empty() {
+                expected = search_params.clone();
+            }
+            assert_eq!(expected, search_params);
+        }
+        assert_eq!("a=1&KEY=Value&Key=VALUE&kEy=value&key=value", expected);
+    }
+
+    #[test]
+    fn nth_param_test() {
+        let search_params = "first=1&=skipped&second=two%20words&third";
+
+        let boxed_param = nth_param(search_params, 0);
+        assert_eq!(Some(("first".to_string(), "1".to_string())), boxed_param);
+
+        let boxed_param = nth_param(search_params, 1);
+        assert_eq!(Some(("second".to_string(), "two words".to_string())), boxed_param);
+
+        let boxed_param = nth_param(search_params, 2);
+        assert_eq!(Some(("third".to_string(), "".to_string())), boxed_param);
+
+        let boxed_param = nth_param(search_params, 3);
+        assert!(boxed_param.is_none());
+    }
+
+    #[test]
+    fn nth_param_empty() {
+        assert!(nth_param("", 0).is_none());
+        assert!(nth_param("   ", 0).is_none());
+    }
+
+    #[test]
+    fn encode_idempotent() {
+        assert_eq!("a%20b%20c", encode_uri_component_idempotent("a%20b c"));
+        assert_eq!("a%2fb%2F", encode_uri_component_idempotent("a%2fb/"));
+        assert_eq!("%25%252", encode_uri_component_idempotent("%%2"));
+        assert_eq!("%25ZZ%C3%BC", encode_uri_component_idempotent("%ZZü"));
+
+        let encoded = encode_uri_component("key=value & more");
+        assert_eq!(encoded, encode_uri_component_idempotent(&encoded));
+        assert_eq!(encoded, encode_uri_component_idempotent("key=value%20& more"));
+    }
+
+    #[test]
+    fn build_url_search_params_ordered_test() {
+        let params: Vec<(String, String)> = vec![
+            ("z".to_string(), "last letter".to_string()),
+            ("a&b".to_string(), "c=d".to_string()),
+            ("empty".to_string(), "".to_string()),
+        ];
+
+        let search_params = build_url_search_params_ordered(&params);
+        assert_eq!("z=last%20letter&a%26b=c%3Dd&empty=", search_params);
+    }
+
+    #[test]
+    fn chunk_params_test() {
+        let params: Vec<(String, String)> = vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "two words".to_string()),
+            ("c".to_string(), "3".to_string()),
+            ("d".to_string(), "4".to_string()),
+        ];
+
+        let chunks = chunk_params(&params, 13).unwrap();
+        assert_eq!(vec!["a=1", "b=two%20words", "c=3&d=4"], chunks);
+        for chunk in chunks.iter() {
+            assert!(chunk.len() <= 13);
+        }
+
+        let chunks = chunk_params(&params, 17).unwrap();
+        assert_eq!(vec!["a=1&b=two%20words", "c=3&d=4"], chunks);
+
+        let chunks = chunk_params(&params, 1000).unwrap();
+        assert_eq!(vec![build_url_search_params_ordered(&params)], chunks);
+
+        let params: Vec<(String, String)> = vec![];
+        assert!(chunk_params(&params, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn chunk_params_pair_over_budget() {
+        let params: Vec<(String, String)> = vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "two words".to_string()),
+        ];
+
+        let boxed_error = chunk_params(&params, 12);
+        assert!(boxed_error.is_err());
+        let error = boxed_error.err().unwrap();
+        assert_eq!(UrlSearchParamsError::LimitExceeded { limit: "pair bytes", max: 12 }, error);
+    }
+
+    #[test]
+    fn symbol_all_round_trip() {
+        let symbols = Symbol::all();
+        assert_eq!(33, symbols.len());
+
+        for (symbol, encoded) in symbols {
+            assert_eq!(1, symbol.len());
+            assert_eq!(*symbol, decode_uri_component(encoded));
+
+            let encoded_symbol = encode_uri_component(symbol);
+            if needs_encoding(symbol.as_bytes()[0]) {
+                assert_eq!(*encoded, encoded_symbol);
+            } else {
+                assert_eq!(*symbol, encoded_symbol);
+            }
+        }
+
+    }
+
+    #[test]
+    fn symbol_all_covers_every_symbol() {
+        let Symbol {
+            new_line,
+            carriage_return,
+            new_line_carriage_return,
+            empty_string,
+            whitespace,
+            equals,
+            comma,
+            hyphen,
+            slash,
+            semicolon,
+            colon,
+            number_sign,
+            opening_square_bracket,
+            closing_square_bracket,
+            opening_curly_bracket,
+            closing_curly_bracket,
+            quotation_mark,
+            underscore,
+            single_quote,
+            percent,
+            exclamation_mark,
+            dollar,
+            ampersand,
+            opening_bracket,
+            closing_bracket,
+            asterisk,
+            plus,
+            question_mark,
+            at,
+            backtick,
+            vertical_bar,
+            tilde,
+            caret,
+            less_than,
+            greater_than,
+        } = SYMBOL;
+        assert_eq!(SYMBOL.empty_string, empty_string);
+        assert_eq!("\r\n", new_line_carriage_return);
+
+        let symbols = Symbol::all();
+        for symbol in [
+            new_line,
+            carriage_return,
+            whitespace,
+            equals,
+            comma,
+            hyphen,
+            slash,
+            semicolon,
+            colon,
+            number_sign,
+            opening_square_bracket,
+            closing_square_bracket,
+            opening_curly_bracket,
+            closing_curly_bracket,
+            quotation_mark,
+            underscore,
+            single_quote,
+            percent,
+            exclamation_mark,
+            dollar,
+            ampersand,
+            opening_bracket,
+            closing_bracket,
+            asterisk,
+            plus,
+            question_mark,
+            at,
+            backtick,
+            vertical_bar,
+            tilde,
+            caret,
+            less_than,
+            greater_than,
+        ] {
+            assert!(symbols.iter().any(|(known, _)| *known == symbol), "{:?} is missing", symbol);
+        }
+    }
+
+    #[test]
+    fn parse_url_search_params_ordered_test() {
+        let pairs = parse_url_search_params_ordered("sig=abc&z=1&=skipped&a=%D0%BA&z=2&flag");
+
+        let expected = vec![
+            ("sig".to_string(), "abc".to_string()),
+            ("z".to_string(), "1".to_string()),
+            ("a".to_string(), "к".to_string()),
+            ("z".to_string(), "2".to_string()),
+            ("flag".to_string(), "".to_string()),
+        ];
+        assert_eq!(expected, pairs);
+        assert_eq!("sig=abc&z=1&a=%D0%BA&z=2&flag=", build_url_search_params_ordered(&pairs));
+
+        assert!(parse_url_search_params_ordered("").is_empty());
+    }
+
+    #[test]
+    fn parse_url_search_params_multi_test() {
+        let params = parse_url_search_params_multi("tag=a&id=1&tag=b&=x&tag=%20c");
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("tag");
+        assert!(boxed_get.is_some());
+
+        let actual_values = boxed_get.unwrap();
+        assert_eq!(&vec!["a".to_string(), "b".to_string(), " c".to_string()], actual_values);
+
+        let boxed_get = params.get("id");
+        assert!(boxed_get.is_some());
+
+        let actual_values = boxed_get.unwrap();
+        assert_eq!(&vec!["1".to_string()], actual_values);
+
+        assert!(parse_url_search_params_multi("").is_empty());
+    }
+
+    #[test]
+    fn build_from_pairs_test() {
+        let borrowed: Vec<(&str, &str)> = vec![("z", "last letter"), ("a&b", "c=d")];
+        assert_eq!("z=last%20letter&a%26b=c%3Dd", build_from_pairs(borrowed.iter().copied()));
+
+        let mixed: Vec<(&str, String)> = vec![("key", "value".to_string())];
+        assert_eq!("key=value", build_from_pairs(mixed));
+
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("key".to_string(), "its value".to_string());
+        assert_eq!("key=its%20value", build_from_pairs(params_map.iter()));
+
+        let owned: Vec<(String, String)> = vec![("a".to_string(), "1".to_string()), ("b".to_string(), "".to_string())];
+        assert_eq!(build_url_search_params_ordered(&owned), build_from_pairs(owned.iter().map(|(key, value)| (key, value))));
+
+        let empty: Vec<(&str, &str)> = vec![];
+        assert_eq!("", build_from_pairs(empty));
+    }
+
+    #[test]
+    fn build_url_search_params_multi_test() {
+        let mut params_map: HashMap<String, Vec<String>> = HashMap::new();
+        params_map.insert("tag".to_string(), vec!["a b".to_string(), "c".to_string()]);
+        params_map.insert("Id".to_string(), vec!["1".to_string()]);
+        params_map.insert("empty".to_string(), vec![]);
+        params_map.insert("a&b".to_string(), vec!["".to_string()]);
+
+        let search_params = build_url_search_params_multi(params_map.clone());
+        assert_eq!("a%26b=&Id=1&tag=a%20b&tag=c", search_params);
+
+        params_map.remove("empty");
+        assert_eq!(params_map, parse_url_search_params_multi(&search_params));
+
+        assert_eq!("", build_url_search_params_multi(HashMap::new()));
+    }
+
+    #[test]
+    fn btree_round_trip() {
+        let params = parse_url_search_params_btree("z=last%20letter&a%26b=c%3Dd&Z=1&z=again&=skipped");
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("z");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!("again", actual_param_value);
+
+        let search_params = build_url_search_params_btree(params.clone());
+        assert_eq!("Z=1&a%26b=c%3Dd&z=again", search_params);
+        assert_eq!(params, parse_url_search_params_btree(&search_params));
+
+        assert_eq!("", build_url_search_params_btree(BTreeMap::new()));
+    }
+
+    #[test]
+    fn flag_set_test() {
+        let flags = flag_set("debug&verbose=1&empty=&%64ry%20run&&=&debug");
+        assert_eq!(2, flags.len());
+        assert!(flags.contains("debug"));
+        assert!(flags.contains("dry run"));
+
+        let params = parse_url_search_params("debug&verbose=1");
+        assert_eq!(Some(&"".to_string()), params.get("debug"));
+
+        assert!(flag_set("a=1&b=").is_empty());
+        assert!(flag_set("").is_empty());
+    }
+
+    #[test]
+    fn parse_url_search_params_optional_test() {
+        let params = parse_url_search_params_optional("flag&empty=&a=%20b&=skipped&twice&twice=2&eq==");
+        assert_eq!(5, params.len());
+
+        let boxed_get = params.get("flag");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert!(actual_param_value.is_none());
+
+        assert_eq!(Some(&Some("".to_string())), params.get("empty"));
+        assert_eq!(Some(&Some(" b".to_string())), params.get("a"));
+        assert_eq!(Some(&Some("2".to_string())), params.get("twice"));
+        assert_eq!(Some(&Some("=".to_string())), params.get("eq"));
+
+        let pairs: Vec<(String, Option<String>)> = vec![
+            ("debug".to_string(), None),
+            ("a".to_string(), Some("".to_string())),
+        ];
+        let search_params = build_url_search_params_optional(&pairs);
+        let parsed: HashMap<String, Option<String>> = pairs.into_iter().collect();
+        assert_eq!(parsed, parse_url_search_params_optional(&search_params));
+    }
+
+    #[test]
+    fn build_url_search_params_by_test() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("b".to_string(), "x y".to_string());
+        params_map.insert("C".to_string(), "2".to_string());
+        params_map.insert("a".to_string(), "3".to_string());
+
+        let search_params = build_url_search_params_by(params_map.clone(), |(a, _), (b, _)| a.cmp(b));
+        assert_eq!("C=2&a=3&b=x%20y", search_params);
+
+        let search_params = build_url_search_params_by(params_map.clone(), |(_, a), (_, b)| a.cmp(b));
+        assert_eq!("C=2&a=3&b=x%20y", search_params);
+
+        let search_params = build_url_search_params_by(params_map, |(a, _), (b, _)| b.cmp(a));
+        assert_eq!("b=x%20y&a=3&C=2", search_params);
+    }
+
+    #[test]
+    fn built_len_test() {
+        let params: Vec<(String, String)> = vec![];
+        assert_eq!(0, built_len(&params));
+
+        let params: Vec<(String, String)> = vec![
+            ("key".to_string(), "".to_string()),
+        ];
+        assert_eq!(4, built_len(&params));
+
+        let params: Vec<(String, String)> = vec![
+            ("z".to_string(), "last letter".to_string()),
+            ("a&b".to_string(), "c=d".to_string()),
+            ("ключ".to_string(), "значення".to_string()),
+        ];
+        assert_eq!(build_url_search_params_ordered(&params).len(), built_len(&params));
+    }
+
+    #[test]
+    fn encode_with_hex_case() {
+        assert_eq!("%3D", encode_uri_component_with_hex_case("=", true));
+        assert_eq!("%3d", encode_uri_component_with_hex_case("=", false));
+        assert_eq!(encode_uri_component("ü =~"), encode_uri_component_with_hex_case("ü =~", true));
+        assert_eq!("%c3%bc%20%3d~", encode_uri_component_with_hex_case("ü =~", false));
+        assert_eq!("ü =~", decode_uri_component("%c3%bc%20%3d~"));
+    }
+
+    #[test]
+    fn encode_keeps_unreserved() {
+        assert_eq!("a~b", encode_uri_component("a~b"));
+        assert_eq!("a.b", encode_uri_component("a.b"));
+        assert_eq!("a-b", encode_uri_component("a-b"));
+        assert_eq!("a_b", encode_uri_component("a_b"));
+
+        let unreserved = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+        assert_eq!(unreserved, encode_uri_component(unreserved));
+    }
+
+    #[test]
+    fn values_for_test() {
+        let search_params = "t=a&x=1&t=b&t%20x=c&%74=d";
+
+        let values: Vec<String> = values_for(search_params, "t").collect();
+        assert_eq!(vec!["a", "b", "d"], values);
+
+        let values: Vec<String> = values_for(search_params, "t x").collect();
+        assert_eq!(vec!["c"], values);
+
+        let mut boxed_values = values_for(search_params, "missing");
+        assert!(boxed_values.next().is_none());
+    }
+
+    #[test]
+    fn encode_path_segment_test() {
+        assert_eq!("my%20file%2Fname:v1", encode_path_segment("my file/name:v1"));
+        assert_eq!("a%3Fb%23c%25d", encode_path_segment("a?b#c%d"));
+
+        let pchar = "az09-._~!$&'()*+,;=:@";
+        assert_eq!(pchar, encode_path_segment(pchar));
+
+        assert_eq!("my file/name:v1", decode_uri_component(&encode_path_segment("my file/name:v1")));
+    }
+
+    #[test]
+    fn build_url_search_params_custom_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let mut params_map: HashMap<String, String, BuildHasherDefault<DefaultHasher>> = HashMap::default();
+        params_map.insert("b".to_string(), "2".to_string());
+        params_map.insert("a".to_string(), "1 1".to_string());
+
+        let search_params = build_url_search_params(params_map);
+        assert_eq!("a=1%201&b=2", search_params);
+    }
+
+    #[test]
+    fn try_decode_test() {
+        assert_eq!(Ok("a b&ü".to_string()), try_decode_uri_component("a%20b%26%C3%BC"));
+        assert_eq!(Ok("%".to_string()), try_decode_uri_component("%25"));
+
+        let error = UrlSearchParamsError::Decode { input: "a%ZZ".to_string(), index: 1 };
+        assert_eq!(Err(error), try_decode_uri_component("a%ZZ"));
+
+        let error = UrlSearchParamsError::Decode { input: "a%2".to_string(), index: 1 };
+        assert_eq!(Err(error), try_decode_uri_component("a%2"));
+
+        let error = UrlSearchParamsError::InvalidUtf8("%FF".to_string());
+        assert_eq!(Err(error), try_decode_uri_component("%FF"));
+    }
+
+    #[test]
+    fn try_parse_url_search_params_test() {
+        let params = try_parse_url_search_params("a=1&b=%20&a=2", &ParseOptions::default()).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "2");
+
+        let boxed_params = try_parse_url_search_params("a=1&b=%2", &ParseOptions::default());
+        let error = UrlSearchParamsError::Decode { input: "%2".to_string(), index: 0 };
+        assert_eq!(Err(error), boxed_params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_reject_duplicates() {
+        let options = ParseOptions { reject_duplicates: true, ..ParseOptions::default() };
+
+        let boxed_params = try_parse_url_search_params("a=1&a=2", &options);
+        assert_eq!(Err(UrlSearchParamsError::DuplicateKey("a".to_string())), boxed_params);
+
+        let boxed_params = try_parse_url_search_params("a=1&%61=2", &options);
+        assert_eq!(Err(UrlSearchParamsError::DuplicateKey("a".to_string())), boxed_params);
+
+        let params = try_parse_url_search_params("a=1&b=2", &options).unwrap();
+        assert_eq!(2, params.len());
+    }
+
+    #[test]
+    fn try_parse_url_search_params_aliases() {
+        let mut options = ParseOptions::default();
+        options.aliases.push(("q".to_string(), "query".to_string()));
+        options.aliases.push(("search".to_string(), "query".to_string()));
+
+        let params = try_parse_url_search_params("s%65arch=rust&page=1", &options).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("query");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "rust");
+
+        let params = try_parse_url_search_params("q=a&query=b", &options).unwrap();
+        assert_eq!(1, params.len());
+        assert_eq!(Some(&"b".to_string()), params.get("query"));
+
+        options.reject_duplicates = true;
+        let boxed_params = try_parse_url_search_params("q=a&query=b", &options);
+        assert_eq!(Err(UrlSearchParamsError::DuplicateKey("query".to_string())), boxed_params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_plus_as_space() {
+        let params = try_parse_url_search_params("q+1=rust+lang&sum=1%2B1", &ParseOptions::default()).unwrap();
+        assert_eq!(Some(&"rust+lang".to_string()), params.get("q+1"));
+
+        let options = ParseOptions { plus_as_space: true, ..ParseOptions::default() };
+        let params = try_parse_url_search_params("q+1=rust+lang&sum=1%2B1", &options).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_get = params.get("q 1");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "rust lang");
+
+        let boxed_get = params.get("sum");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "1+1");
+    }
+
+    #[test]
+    fn try_parse_url_search_params_empty_keys() {
+        let params = try_parse_url_search_params("=x&a=1&&", &ParseOptions::default()).unwrap();
+        assert_eq!(1, params.len());
+
+        let options = ParseOptions { reject_empty_keys: true, ..ParseOptions::default() };
+        let boxed_params = try_parse_url_search_params("a=1&=x", &options);
+        assert_eq!(Err(UrlSearchParamsError::MalformedPair("=x".to_string())), boxed_params);
+
+        let boxed_params = try_parse_url_search_params("a=1&=", &options);
+        assert_eq!(Err(UrlSearchParamsError::MalformedPair("=".to_string())), boxed_params);
+
+        let params = try_parse_url_search_params("a=1&&b=2&", &options).unwrap();
+        assert_eq!(2, params.len());
+
+        let options = ParseOptions { reject_empty_keys: true, reject_duplicates: true, ..ParseOptions::default() };
+        let boxed_params = try_parse_url_search_params("a=1&a=2&=x", &options);
+        assert_eq!(Err(UrlSearchParamsError::DuplicateKey("a".to_string())), boxed_params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_max_len() {
+        let options = ParseOptions { max_len: Some(7), ..ParseOptions::default() };
+
+        let params = try_parse_url_search_params("a=1&b=2", &options).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_params = try_parse_url_search_params("a=1&b=22", &options);
+        let error: ParseError = UrlSearchParamsError::LimitExceeded { limit: "input bytes", max: 7 };
+        assert_eq!(Err(error), boxed_params);
+    }
+
+    #[test]
+    fn decode_uri_component_with_test() {
+        assert_eq!(Ok("a b".to_string()), decode_uri_component_with("a%20b", DecodeMode::Strict));
+        assert_eq!(Ok("a b".to_string()), decode_uri_component_with("a%20b", DecodeMode::Lossy));
+
+        for malformed in ["%ZZ", "100%", "%2", "%%41"] {
+            assert!(decode_uri_component_with(malformed, DecodeMode::Strict).is_err());
+            assert_eq!(Ok(decode_uri_component(malformed)), decode_uri_component_with(malformed, DecodeMode::Lossy));
+        }
+
+        assert_eq!(Err(UrlSearchParamsError::InvalidUtf8("%FF".to_string())), decode_uri_component_with("%FF", DecodeMode::Strict));
+        assert_eq!(Ok("\u{FFFD}".to_string()), decode_uri_component_with("%FF", DecodeMode::Lossy));
+    }
+
+    #[test]
+    fn try_parse_url_search_params_lossy() {
+        let boxed_params = try_parse_url_search_params("a=%ZZ&b=100%", &ParseOptions::default());
+        assert!(boxed_params.is_err());
+
+        let options = ParseOptions { decode_mode: DecodeMode::Lossy, ..ParseOptions::default() };
+        let params = try_parse_url_search_params("a=%ZZ&b=100%&c=%20", &options).unwrap();
+        assert_eq!(parse_url_search_params("a=%ZZ&b=100%&c=%20"), params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_separators() {
+        let params = try_parse_url_search_params("a=1;b=2", &ParseOptions::default()).unwrap();
+        assert_eq!(1, params.len());
+        assert_eq!(Some(&"1;b=2".to_string()), params.get("a"));
+
+        let options = ParseOptions { separators: vec!['&', ';'], ..ParseOptions::default() };
+        let params = try_parse_url_search_params("a=1;b=2&c=%3B", &options).unwrap();
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "2");
+
+        assert_eq!(Some(&";".to_string()), params.get("c"));
+
+        let options = ParseOptions { separators: vec![';'], reject_empty_keys: true, ..ParseOptions::default() };
+        let boxed_params = try_parse_url_search_params("a=1&b=2;=x", &options);
+        assert_eq!(Err(UrlSearchParamsError::MalformedPair("=x".to_string())), boxed_params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_strip_question_mark() {
+        let params = try_parse_url_search_params("?a=1&b=2", &ParseOptions::default()).unwrap();
+        assert!(params.contains_key("?a"));
+
+        let options = ParseOptions { strip_question_mark: true, ..ParseOptions::default() };
+        let params = try_parse_url_search_params("?a=1&b=2", &options).unwrap();
+        assert_eq!(2, params.len());
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
-    use crate::{build_url_search_params, decode_uri_component, encode_uri_component, parse_url_search_params};
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "1");
+
+        let params = try_parse_url_search_params("??a=1", &options).unwrap();
+        assert!(params.contains_key("?a"));
+
+        let params = try_parse_url_search_params("a=1?", &options).unwrap();
+        assert_eq!(Some(&"1?".to_string()), params.get("a"));
+
+        assert!(try_parse_url_search_params("?", &options).unwrap().is_empty());
+    }
 
     #[test]
-    fn build_url_search_params_test() {
-        let mut params_map: HashMap<String, String> = HashMap::new();
-        params_map.insert("key1".to_string(), "test1".to_string());
-        params_map.insert("key2".to_string(), "test2".to_string());
-        params_map.insert("".to_string(), "empty_key".to_string());
-        params_map.insert("empty_value".to_string(), "".to_string());
+    fn parse_from_url_test() {
+        let (path, params, fragment) = parse_from_url("https://example.com/a/b?x=1&y=%20#frag");
+        assert_eq!("https://example.com/a/b", path);
+        assert_eq!(2, params.len());
+        assert_eq!(Some("frag"), fragment);
 
-        let search_params = build_url_search_params(params_map);
-        let parsed_search_params = parse_url_search_params(&search_params);
+        let boxed_get = params.get("y");
+        assert!(boxed_get.is_some());
 
-        let boxed_get = parsed_search_params.get("key1");
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, " ");
+
+        let (path, params, fragment) = parse_from_url("https://example.com/#/route?tab=1");
+        assert_eq!("https://example.com/", path);
+        assert!(params.is_empty());
+        assert_eq!(Some("/route?tab=1"), fragment);
+
+        let (path, params, fragment) = parse_from_url("/path?a=1?b=2");
+        assert_eq!("/path", path);
+        assert_eq!(Some(&"1?b=2".to_string()), params.get("a"));
+        assert!(fragment.is_none());
+
+        let (path, params, fragment) = parse_from_url("");
+        assert_eq!("", path);
+        assert!(params.is_empty());
+        assert!(fragment.is_none());
+    }
+
+    #[test]
+    fn parse_fragment_params_test() {
+        let params = parse_fragment_params("#access_token=2YotnFZFEjr1zCsicMWpAA&state=xyz+1&expires_in=3600");
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("access_token");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "test1");
+        assert_eq!(actual_param_value, "2YotnFZFEjr1zCsicMWpAA");
 
-        let boxed_get = parsed_search_params.get("key2");
+        assert_eq!(Some(&"xyz 1".to_string()), params.get("state"));
+        assert_eq!(params, parse_fragment_params("access_token=2YotnFZFEjr1zCsicMWpAA&state=xyz+1&expires_in=3600"));
+
+        assert!(parse_fragment_params("#").is_empty());
+        assert!(parse_fragment_params("").is_empty());
+        assert!(parse_fragment_params("##a=1").contains_key("#a"));
+    }
+
+    #[test]
+    fn try_parse_url_search_params_html_entities() {
+        let query = "a=1&amp;b=2&#38;c=3&#x26;d=%26amp%3B";
+
+        let params = try_parse_url_search_params(query, &ParseOptions::default()).unwrap();
+        assert!(params.contains_key("amp;b"));
+
+        let options = ParseOptions { html_entities: true, ..ParseOptions::default() };
+        let params = try_parse_url_search_params(query, &options).unwrap();
+        assert_eq!(4, params.len());
+
+        let boxed_get = params.get("b");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "test2");
+        assert_eq!(actual_param_value, "2");
 
-        let boxed_get = parsed_search_params.get("");
-        assert!(boxed_get.is_none());
+        assert_eq!(Some(&"3".to_string()), params.get("c"));
+        assert_eq!(Some(&"&amp;".to_string()), params.get("d"));
+    }
 
-        let boxed_get = parsed_search_params.get("empty_value");
+    #[test]
+    fn try_parse_url_search_params_padded_html_entities() {
+        let options = ParseOptions { html_entities: true, ..ParseOptions::default() };
+
+        for query in ["a=1&#038;b=2", "a=1&#0038;b=2", "a=1&#x026;b=2", "a=1&#X0026;b=2"] {
+            let params = try_parse_url_search_params(query, &options).unwrap();
+            assert_eq!(2, params.len());
+
+            let boxed_get = params.get("b");
+            assert!(boxed_get.is_some());
+
+            let actual_param_value = boxed_get.unwrap();
+            assert_eq!(actual_param_value, "2");
+        }
+
+        let params = try_parse_url_search_params("a=1&#3;b=2&#x38;c=3", &options).unwrap();
+        assert!(params.contains_key("#3;b"));
+        assert!(params.contains_key("#x38;c"));
+    }
+
+    #[test]
+    fn try_parse_url_search_params_double_escaped_html_entities() {
+        let options = ParseOptions { html_entities: true, ..ParseOptions::default() };
+
+        let params = try_parse_url_search_params("a=1&amp;#38;b=2", &options).unwrap();
+        assert_eq!(2, params.len());
+        assert!(params.contains_key("#38;b"));
+        assert!(!params.contains_key("b"));
+
+        let params = try_parse_url_search_params("a=x&amp;amp;b=2", &options).unwrap();
+        assert!(params.contains_key("amp;b"));
+    }
+
+    #[test]
+    fn decode_uri_component_utf8_test() {
+        for policy in [Utf8Policy::Replace, Utf8Policy::KeepEscaped, Utf8Policy::Error] {
+            assert_eq!(Ok("ключ 100%".to_string()), decode_uri_component_utf8("%D0%BA%D0%BB%D1%8E%D1%87%20100%", policy));
+        }
+
+        let input = "%C3%28x%E2%82%FF%D0%BA%80";
+        assert_eq!(Ok("\u{FFFD}(x\u{FFFD}\u{FFFD}к\u{FFFD}".to_string()), decode_uri_component_utf8(input, Utf8Policy::Replace));
+        assert_eq!(Ok(decode_uri_component(input)), decode_uri_component_utf8(input, Utf8Policy::Replace));
+        assert_eq!(Ok("%C3(x%E2%82%FFк%80".to_string()), decode_uri_component_utf8(input, Utf8Policy::KeepEscaped));
+        assert_eq!(Err(UrlSearchParamsError::InvalidUtf8(input.to_string())), decode_uri_component_utf8(input, Utf8Policy::Error));
+
+        assert_eq!(Ok("%FFé".to_string()), decode_uri_component_utf8("%ffé", Utf8Policy::KeepEscaped));
+    }
+
+    #[test]
+    fn try_parse_url_search_params_utf8_policy() {
+        let boxed_params = try_parse_url_search_params("a=%FF", &ParseOptions::default());
+        assert_eq!(Err(UrlSearchParamsError::InvalidUtf8("%FF".to_string())), boxed_params);
+
+        let options = ParseOptions { utf8_policy: Some(Utf8Policy::KeepEscaped), ..ParseOptions::default() };
+        let params = try_parse_url_search_params("a=%FF%20", &options).unwrap();
+        assert_eq!(Some(&"%FF ".to_string()), params.get("a"));
+
+        let boxed_params = try_parse_url_search_params("a=%FF&b=%ZZ", &options);
+        assert_eq!(Err(UrlSearchParamsError::Decode { input: "%ZZ".to_string(), index: 0 }), boxed_params);
+
+        let options = ParseOptions { utf8_policy: Some(Utf8Policy::Replace), ..ParseOptions::default() };
+        let params = try_parse_url_search_params("a=%FF", &options).unwrap();
+        assert_eq!(Some(&"\u{FFFD}".to_string()), params.get("a"));
+
+        let options = ParseOptions { decode_mode: DecodeMode::Lossy, utf8_policy: Some(Utf8Policy::Error), ..ParseOptions::default() };
+        let boxed_params = try_parse_url_search_params("a=%ZZ&b=%FF", &options);
+        assert_eq!(Err(UrlSearchParamsError::InvalidUtf8("%FF".to_string())), boxed_params);
+    }
+
+    #[test]
+    fn try_parse_url_search_params_limits() {
+        let options = ParseOptions {
+            max_pairs: Some(2),
+            max_key_len: Some(3),
+            max_value_len: Some(6),
+            ..ParseOptions::default()
+        };
+
+        let params = try_parse_url_search_params("abc=%20%20&&b=123456&", &options).unwrap();
+        assert_eq!(2, params.len());
+
+        let boxed_params = try_parse_url_search_params("a=1&b=2&c=3", &options);
+        assert_eq!(Err(UrlSearchParamsError::LimitExceeded { limit: "pairs", max: 2 }), boxed_params);
+
+        let boxed_params = try_parse_url_search_params("=x&=y&a=1", &options);
+        assert_eq!(Err(UrlSearchParamsError::LimitExceeded { limit: "pairs", max: 2 }), boxed_params);
+
+        let boxed_params = try_parse_url_search_params("abcd=1", &options);
+        assert_eq!(Err(UrlSearchParamsError::LimitExceeded { limit: "key bytes", max: 3 }), boxed_params);
+
+        let boxed_params = try_parse_url_search_params("a=%20%20%20", &options);
+        assert_eq!(Err(UrlSearchParamsError::LimitExceeded { limit: "value bytes", max: 6 }), boxed_params);
+
+        let huge = "a=1&".repeat(10_000);
+        let options = ParseOptions { max_len: Some(1024), ..ParseOptions::default() };
+        let boxed_params = try_parse_url_search_params(&huge, &options);
+        assert_eq!(Err(UrlSearchParamsError::LimitExceeded { limit: "input bytes", max: 1024 }), boxed_params);
+
+        let options = ParseOptions { max_len: Some(3), strip_question_mark: true, ..ParseOptions::default() };
+        assert!(try_parse_url_search_params("?a=1", &options).is_err());
+    }
+
+    #[test]
+    fn split_query_fragment_test() {
+        assert_eq!(("a=1&b=2", Some("section")), split_query_fragment("?a=1&b=2#section"));
+        assert_eq!(("a=1", None), split_query_fragment("?a=1"));
+        assert_eq!(("", Some("section?b=2")), split_query_fragment("#section?b=2"));
+        assert_eq!(("", Some("")), split_query_fragment("?#"));
+        assert_eq!(("", None), split_query_fragment(""));
+    }
+
+    #[test]
+    fn parse_into_reuses_vec() {
+        let mut pairs: Vec<(String, String)> = Vec::with_capacity(8);
+        let capacity = pairs.capacity();
+
+        parse_into("t=a&=skipped&x=1&t=b%20c", &mut pairs);
+        assert_eq!(vec![
+            ("t".to_string(), "a".to_string()),
+            ("x".to_string(), "1".to_string()),
+            ("t".to_string(), "b c".to_string()),
+        ], pairs);
+
+        parse_into("", &mut pairs);
+        assert!(pairs.is_empty());
+        assert_eq!(capacity, pairs.capacity());
+    }
+
+    #[test]
+    fn encode_decode_whitespace_control_bytes() {
+        assert_eq!("\t", decode_uri_component("%09"));
+        assert_eq!("\u{0B}", decode_uri_component("%0B"));
+        assert_eq!("\u{0C}", decode_uri_component("%0C"));
+
+        let component = "line 1\tcolumn\r\nline 2\u{0B}\u{0C}end";
+        let encoded = encode_uri_component(component);
+        assert_eq!("line%201%09column%0D%0Aline%202%0B%0Cend", encoded);
+        assert_eq!(component, decode_uri_component(&encoded));
+
+        let ascii: String = (0u8..=127).map(char::from).collect();
+        assert_eq!(ascii, decode_uri_component(&encode_uri_component(&ascii)));
+    }
+
+    #[test]
+    fn parse_repeated_equals() {
+        let params = parse_url_search_params("a==b&c=&d=e=f=");
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("a");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "=b");
+
+        let boxed_get = params.get("c");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
         assert_eq!(actual_param_value, "");
+
+        let boxed_get = params.get("d");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "e=f=");
     }
 
     #[test]
-    fn build_url_search_params_ampersand() {
+    fn parse_base64_padding() {
+        let query = "token=abc=def&sig=aGVsbG8gd29ybGQ=&pad=YQ==";
+
+        let params = parse_url_search_params(query);
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("token");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "abc=def");
+
+        let boxed_get = params.get("pad");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "YQ==");
+
+        let strict_params = try_parse_url_search_params(query, &ParseOptions::default()).unwrap();
+        assert_eq!(params, strict_params);
+
+        let pairs = parse_url_search_params_ordered(query);
+        assert_eq!(("sig".to_string(), "aGVsbG8gd29ybGQ=".to_string()), pairs[1]);
+
+        let search_params = build_url_search_params_ordered(&pairs);
+        assert_eq!("token=abc%3Ddef&sig=aGVsbG8gd29ybGQ%3D&pad=YQ%3D%3D", search_params);
+        assert_eq!(pairs, parse_url_search_params_ordered(&search_params));
+    }
+
+    #[test]
+    fn write_url_search_params_matches_ordered_builder() {
+        let params: Vec<(String, String)> = vec![
+            ("z".to_string(), "last letter".to_string()),
+            ("a&b".to_string(), "c=d".to_string()),
+            ("ключ".to_string(), "значення~".to_string()),
+            ("empty".to_string(), "".to_string()),
+        ];
+
+        let mut search_params = String::new();
+        let result = write_url_search_params(&params, &mut search_params);
+        assert!(result.is_ok());
+        assert_eq!(build_url_search_params_ordered(&params), search_params);
+
+        let mut search_params = String::new();
+        let empty: [(&str, &str); 0] = [];
+        let result = write_url_search_params(&empty, &mut search_params);
+        assert!(result.is_ok());
+        assert_eq!("", search_params);
+    }
+
+    #[test]
+    fn decode_one_test() {
+        assert_eq!(("a&b".to_string(), 5), decode_one("a%26b&c"));
+        assert_eq!(("a=b".to_string(), 5), decode_one("a%3Db=c"));
+        assert_eq!(("".to_string(), 0), decode_one("&a"));
+        assert_eq!(("a b".to_string(), 5), decode_one("a%20b"));
+        assert_eq!(("".to_string(), 0), decode_one(""));
+
+        let input = "k%201=v%261&k2";
+        let mut tokens: Vec<String> = vec![];
+        let mut position = 0;
+        loop {
+            let (token, consumed) = decode_one(&input[position..]);
+            tokens.push(token);
+            position += consumed;
+            if position >= input.len() {
+                break;
+            }
+            position += 1;
+        }
+        assert_eq!(vec!["k 1", "v&1", "k2"], tokens);
+    }
+
+    #[test]
+    fn parse_with_decoder_test() {
+        let pairs = parse_with_decoder("a=%20&=skipped&b=c=d", |raw| raw.to_string());
+        assert_eq!(vec![
+            ("a".to_string(), "%20".to_string()),
+            ("b".to_string(), "c=d".to_string()),
+        ], pairs);
+
+        let pairs = parse_with_decoder("a=~x~y", |raw| raw.replace('~', "-"));
+        assert_eq!(vec![("a".to_string(), "-x-y".to_string())], pairs);
+
+        let mut parsed_into: Vec<(String, String)> = vec![];
+        parse_into("a=%20&b=c%26", &mut parsed_into);
+        assert_eq!(parsed_into, parse_with_decoder("a=%20&b=c%26", decode_uri_component));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn build_parse_round_trip(params_map in proptest::collection::hash_map("(?s).+", "(?s).*", 0..10)) {
+            let search_params = build_url_search_params(params_map.clone());
+            let parsed_search_params = parse_url_search_params(&search_params);
+            proptest::prop_assert_eq!(params_map, parsed_search_params);
+        }
+
+        #[test]
+        fn build_ordered_parse_round_trip(params in proptest::collection::vec(("(?s).+", "(?s).*"), 0..10)) {
+            let search_params = build_url_search_params_ordered(&params);
+            proptest::prop_assert_eq!(params.len(), search_params.matches('=').count());
+
+            let mut parsed_search_params: Vec<(String, String)> = vec![];
+            parse_into(&search_params, &mut parsed_search_params);
+            proptest::prop_assert_eq!(params, parsed_search_params);
+        }
+    }
+
+    #[test]
+    fn any_test() {
+        let search_params = "a=1&b=hello%20world&c=";
+
+        assert!(any(search_params, |_, value| value.is_empty()));
+        assert!(any(search_params, |_, value| value.len() > 10));
+        assert!(!any(search_params, |_, value| value.len() > 11));
+        assert!(!any("", |_, _| true));
+
+        let visited = std::cell::Cell::new(0);
+        let found = any(search_params, |key, _| {
+            visited.set(visited.get() + 1);
+            key == "b"
+        });
+        assert!(found);
+        assert_eq!(2, visited.get());
+    }
+
+    #[test]
+    fn key_set_test() {
+        let keys = key_set("a=1&b=2&a=3");
+        assert_eq!(2, keys.len());
+        assert!(keys.contains("a"));
+        assert!(keys.contains("b"));
+
+        let keys = key_set("a%20b=%ZZ&=skipped&%61");
+        assert_eq!(2, keys.len());
+        assert!(keys.contains("a b"));
+        assert!(keys.contains("a"));
+
+        assert!(key_set("").is_empty());
+    }
+
+    #[test]
+    fn build_from_display_test() {
+        enum Sort {
+            Ascending,
+            Descending,
+        }
+
+        impl std::fmt::Display for Sort {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Sort::Ascending => write!(f, "price asc"),
+                    Sort::Descending => write!(f, "price desc"),
+                }
+            }
+        }
+
+        let search_params = build_from_display(vec![("page", 2u32), ("limit", 50u32)]);
+        assert_eq!("page=2&limit=50", search_params);
+
+        let search_params = build_from_display([("sort", Sort::Ascending), ("then", Sort::Descending)]);
+        assert_eq!("sort=price%20asc&then=price%20desc", search_params);
+
+        let empty: Vec<(String, u32)> = vec![];
+        assert_eq!("", build_from_display(empty));
+    }
+
+    #[test]
+    fn encode_decode_nul() {
+        assert_eq!("%00", encode_uri_component("\0"));
+        assert_eq!("a%00b", encode_uri_component("a\0b"));
+
+        let decoded = decode_uri_component("%00");
+        assert_eq!("\0", decoded);
+        assert_eq!(1, decoded.len());
+
         let mut params_map: HashMap<String, String> = HashMap::new();
-        params_map.insert("key1".to_string(), "test1".to_string());
-        params_map.insert("key2".to_string(), "test2".to_string());
-        params_map.insert("".to_string(), "empty_key".to_string());
-        params_map.insert("empty_value".to_string(), "".to_string());
-        params_map.insert("&".to_string(), "unescaped_ampersand_as_key".to_string());
+        params_map.insert("key\0".to_string(), "before\0after".to_string());
 
         let search_params = build_url_search_params(params_map);
+        assert_eq!("key%00=before%00after", search_params);
+
         let parsed_search_params = parse_url_search_params(&search_params);
 
-        let boxed_get = parsed_search_params.get("key1");
+        let boxed_get = parsed_search_params.get("key\0");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "test1");
+        assert_eq!(actual_param_value, "before\0after");
+    }
 
-        let boxed_get = parsed_search_params.get("key2");
+    #[test]
+    fn first_raw_test() {
+        let search_params = "a%20b=1&a=x%26y&a=2&empty=&flag";
+
+        assert_eq!(Some("x%26y"), first_raw(search_params, "a"));
+        assert_eq!(Some("1"), first_raw(search_params, "a%20b"));
+        assert_eq!(None, first_raw(search_params, "a b"));
+        assert_eq!(Some(""), first_raw(search_params, "empty"));
+        assert_eq!(Some(""), first_raw(search_params, "flag"));
+        assert_eq!(None, first_raw("", "a"));
+    }
+
+    #[test]
+    fn take_params_test() {
+        let search_params = "q=a%20b&=skipped&page=2&sort=%7E";
+
+        assert_eq!("q=a%20b", take_params(search_params, 1));
+        assert_eq!("q=a%20b&page=2", take_params(search_params, 2));
+        assert_eq!("q=a%20b&page=2&sort=~", take_params(search_params, 3));
+        assert_eq!("q=a%20b&page=2&sort=~", take_params(search_params, 100));
+        assert_eq!("", take_params(search_params, 0));
+        assert_eq!("", take_params("", 1));
+    }
+
+    #[test]
+    fn parse_matrix_params_test() {
+        let params = parse_matrix_params("color=red;size=xl;note=a%3Bb&c");
+        assert_eq!(3, params.len());
+
+        let boxed_get = params.get("color");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "test2");
+        assert_eq!(actual_param_value, "red");
 
-        let boxed_get = parsed_search_params.get("");
-        assert!(boxed_get.is_none());
+        let boxed_get = params.get("size");
+        assert!(boxed_get.is_some());
 
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "xl");
 
-        let boxed_get = parsed_search_params.get("empty_value");
+        let boxed_get = params.get("note");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "");
+        assert_eq!(actual_param_value, "a;b&c");
 
-        let boxed_get = parsed_search_params.get("empty_value");
-        assert!(boxed_get.is_some());
+        assert!(parse_matrix_params("").is_empty());
+    }
+
+    #[test]
+    fn build_url_search_params_ordered_borrowed() {
+        use std::borrow::Cow;
+        use std::rc::Rc;
+
+        let params = [("q", "rust lang"), ("page", "2")];
+        assert_eq!("q=rust%20lang&page=2", build_url_search_params_ordered(&params));
+        assert_eq!(20, built_len(&params));
 
+        let params: Vec<(Cow<str>, Rc<str>)> = vec![(Cow::Borrowed("q"), Rc::from("a&b"))];
+        assert_eq!("q=a%26b", build_url_search_params_ordered(&params));
+
+        let params: Vec<(&str, String)> = vec![("q", "x".to_string())];
+        let mut search_params = String::new();
+        let result = write_url_search_params(&params, &mut search_params);
+        assert!(result.is_ok());
+        assert_eq!("q=x", search_params);
     }
 
     #[test]
-    fn parse_empty_url_search_params() {
-        let search_params = "";
-        let params = parse_url_search_params(search_params);
-        assert_eq!(0, params.len());
+    fn encode_checked() {
+        assert_eq!(("Az09-._~".to_string(), false), encode_uri_component_checked("Az09-._~"));
+        assert_eq!(("".to_string(), false), encode_uri_component_checked(""));
+        assert_eq!(("a%20b".to_string(), true), encode_uri_component_checked("a b"));
+        assert_eq!(("%C3%BC".to_string(), true), encode_uri_component_checked("ü"));
     }
 
     #[test]
-    fn parse_empty_equals_ampersand_search_params() {
-        let search_params = "=&key2=value2";
-        let params = parse_url_search_params(search_params);
-        assert_eq!(1, params.len());
+    fn parse_decodes_once() {
+        let params = parse_url_search_params("a=%2525&b=%252A&c%2526=%2520");
 
-        let boxed_get = params.get("key2");
+        let boxed_get = params.get("a");
         assert!(boxed_get.is_some());
 
         let actual_param_value = boxed_get.unwrap();
-        assert_eq!(actual_param_value, "value2");
+        assert_eq!(actual_param_value, "%25");
+
+        let boxed_get = params.get("b");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "%2A");
+
+        let boxed_get = params.get("c%26");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "%20");
     }
 
     #[test]
-    fn encode_decode() {
-        let component = "\r\n \"%!#$&'()*+,/:;=?@[]][@?=;:/,+*)('&$#!%\" \r\n";
-        let mut _result = encode_uri_component(component);
-        assert_eq!("%0D%0A%20%22%25%21%23%24%26%27%28%29%2A%2B%2C%2F%3A%3B%3D?%40%5B%5D%5D%5B%40?%3D%3B%3A%2F%2C%2B%2A%29%28%27%26%24%23%21%25%22%20%0D%0A", _result);
-        _result = decode_uri_component(_result.as_str());
-        assert_eq!(component, _result);
+    fn parse_grouped_ordered_test() {
+        let groups = parse_grouped_ordered("b=1&a=2&b=3&%62=4&=x&c");
+        assert_eq!(vec![
+            ("b".to_string(), vec!["1".to_string(), "3".to_string(), "4".to_string()]),
+            ("a".to_string(), vec!["2".to_string()]),
+            ("c".to_string(), vec!["".to_string()]),
+        ], groups);
+
+        assert!(parse_grouped_ordered("").is_empty());
+    }
+
+    #[test]
+    fn encode_fragment_test() {
+        assert_eq!("a/b?c%20d", encode_fragment("a/b?c d"));
+        assert_eq!("%23section%25", encode_fragment("#section%"));
+
+        let permitted = "az09-._~!$&'()*+,;=:@/?";
+        assert_eq!(permitted, encode_fragment(permitted));
+
+        assert_eq!("/path?q=a b", decode_uri_component(&encode_fragment("/path?q=a b")));
+    }
+
+    #[test]
+    fn encode_space() {
+        assert_eq!("a%20b", encode_uri_component("a b"));
+        assert_eq!("a+b", encode_uri_component_space_as_plus("a b"));
+        assert_eq!("1%2B1+%3D+2", encode_uri_component_space_as_plus("1+1 = 2"));
     }
 }
@@ -0,0 +1,53 @@
+/// Create [UrlSearchParams](crate::UrlSearchParams) from `key => value`
+/// pairs of any `Display` types, keeping their order
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::search_params;
+///
+/// let params = search_params! {
+///     "q" => "rust url",
+///     "page" => 2,
+/// };
+///
+/// assert_eq!(params.to_string(), "q=rust%20url&page=2");
+/// ```
+#[macro_export]
+macro_rules! search_params {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut params = $crate::UrlSearchParams::new();
+        $(
+            params.append(&$key.to_string(), &$value.to_string());
+        )*
+        params
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::UrlSearchParams;
+
+    #[test]
+    fn search_params() {
+        let page = 2;
+        let params = search_params! {
+            "q" => "a&b",
+            "page" => page,
+            String::from("tag") => 'x',
+            "tag" => 1.5,
+        };
+
+        assert_eq!(4, params.len());
+        assert_eq!(Some("a&b"), params.get("q"));
+        assert_eq!(vec!["x", "1.5"], params.get_all("tag"));
+        assert_eq!("q=a%26b&page=2&tag=x&tag=1.5", params.to_string());
+
+        let single = search_params!("a" => 1);
+        assert_eq!("a=1", single.to_string());
+
+        let empty = search_params!();
+        assert_eq!(UrlSearchParams::new(), empty);
+    }
+}
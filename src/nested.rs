@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use crate::{decode_uri_component, raw_pairs};
+
+/// Value of the parameter tree built by [parse_nested]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestedValue {
+    Leaf(String),
+    Map(HashMap<String, NestedValue>),
+}
+
+/// Convert given string into a tree of parameters, splitting decoded keys
+/// by the given delimiter
+///
+/// Returned value is always a [NestedValue::Map]. Parameters are applied in
+/// the order they appear, so in case of conflicts the last one wins: a leaf
+/// replaces a previously built map with the same path and vice versa, for
+/// example `a=1&a.b=2` yields map `a` with leaf `b` while `a.b=2&a=1` yields
+/// leaf `a`.
+///
+/// Empty path segments are kept as empty keys, so a trailing delimiter, as
+/// in `a.=1`, yields map `a` with leaf under the empty key.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_nested, NestedValue};
+///
+/// let tree = parse_nested("user.name=Bob&user.age=9", '.');
+///
+/// if let NestedValue::Map(root) = tree {
+///     let boxed_user = root.get("user");
+///     assert!(boxed_user.is_some());
+///
+///     if let Some(NestedValue::Map(user)) = boxed_user {
+///         assert_eq!(user.get("name"), Some(&NestedValue::Leaf("Bob".to_string())));
+///         assert_eq!(user.get("age"), Some(&NestedValue::Leaf("9".to_string())));
+///     }
+/// }
+/// ```
+pub fn parse_nested(params: &str, delimiter: char) -> NestedValue {
+    let mut root : HashMap<String, NestedValue> = HashMap::new();
+
+    for (key, value) in raw_pairs(params) {
+        let key = decode_uri_component(key);
+        let value = decode_uri_component(value);
+
+        let mut path : Vec<&str> = key.split(delimiter).collect();
+        let last = path.pop().unwrap_or_default();
+
+        let mut parents : Vec<(&str, HashMap<String, NestedValue>)> = Vec::with_capacity(path.len());
+        let mut node = std::mem::take(&mut root);
+        for segment in path {
+            let child = match node.remove(segment) {
+                Some(NestedValue::Map(map)) => map,
+                Some(NestedValue::Leaf(_)) | None => HashMap::new(),
+            };
+            parents.push((segment, node));
+            node = child;
+        }
+
+        node.insert(last.to_string(), NestedValue::Leaf(value));
+        while let Some((segment, mut parent)) = parents.pop() {
+            parent.insert(segment.to_string(), NestedValue::Map(node));
+            node = parent;
+        }
+        root = node;
+    }
+
+    NestedValue::Map(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{parse_nested, NestedValue};
+
+    fn leaf(value: &str) -> NestedValue {
+        NestedValue::Leaf(value.to_string())
+    }
+
+    #[test]
+    fn parse_nested_test() {
+        let tree = parse_nested("user.name=Bob&user.age=9&user.address.city=Kyiv&page=2", '.');
+
+        let mut address: HashMap<String, NestedValue> = HashMap::new();
+        address.insert("city".to_string(), leaf("Kyiv"));
+
+        let mut user: HashMap<String, NestedValue> = HashMap::new();
+        user.insert("name".to_string(), leaf("Bob"));
+        user.insert("age".to_string(), leaf("9"));
+        user.insert("address".to_string(), NestedValue::Map(address));
+
+        let mut root: HashMap<String, NestedValue> = HashMap::new();
+        root.insert("user".to_string(), NestedValue::Map(user));
+        root.insert("page".to_string(), leaf("2"));
+
+        assert_eq!(NestedValue::Map(root), tree);
+    }
+
+    #[test]
+    fn parse_nested_encoded_delimiter() {
+        let tree = parse_nested("a%2Eb=1", '.');
+
+        let mut a: HashMap<String, NestedValue> = HashMap::new();
+        a.insert("b".to_string(), leaf("1"));
+
+        let mut root: HashMap<String, NestedValue> = HashMap::new();
+        root.insert("a".to_string(), NestedValue::Map(a));
+
+        assert_eq!(NestedValue::Map(root), tree);
+    }
+
+    #[test]
+    fn parse_nested_conflicts_last_wins() {
+        let tree = parse_nested("a=1&a.b=2", '.');
+
+        let mut a: HashMap<String, NestedValue> = HashMap::new();
+        a.insert("b".to_string(), leaf("2"));
+
+        let mut root: HashMap<String, NestedValue> = HashMap::new();
+        root.insert("a".to_string(), NestedValue::Map(a));
+
+        assert_eq!(NestedValue::Map(root), tree);
+
+        let tree = parse_nested("a.b=2&a=1", '.');
+
+        let mut root: HashMap<String, NestedValue> = HashMap::new();
+        root.insert("a".to_string(), leaf("1"));
+
+        assert_eq!(NestedValue::Map(root), tree);
+    }
+
+    #[test]
+    fn parse_nested_trailing_delimiter() {
+        let tree = parse_nested("a.=1&a.b=2&c%5B%5D=3", '.');
+
+        let mut a: HashMap<String, NestedValue> = HashMap::new();
+        a.insert("".to_string(), leaf("1"));
+        a.insert("b".to_string(), leaf("2"));
+
+        let mut root: HashMap<String, NestedValue> = HashMap::new();
+        root.insert("a".to_string(), NestedValue::Map(a));
+        root.insert("c[]".to_string(), leaf("3"));
+
+        assert_eq!(NestedValue::Map(root), tree);
+    }
+
+    #[test]
+    fn parse_nested_empty() {
+        assert_eq!(NestedValue::Map(HashMap::new()), parse_nested("", '.'));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{decode_uri_component_utf8, decode_uri_component_with, find_malformed_escape, UrlSearchParamsError};
+
+/// How malformed percent-encoding, such as `%ZZ` or trailing `%`, is handled
+/// by [decode_uri_component_with](crate::decode_uri_component_with) and
+/// [ParseOptions::decode_mode]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Fail on malformed escapes and invalid UTF-8, same as
+    /// [try_decode_uri_component](crate::try_decode_uri_component)
+    #[default]
+    Strict,
+    /// Keep malformed escapes as is and replace invalid UTF-8, same as
+    /// [decode_uri_component](crate::decode_uri_component)
+    Lossy,
+}
+
+/// How decoded bytes which are not valid UTF-8 are handled by
+/// [decode_uri_component_utf8](crate::decode_uri_component_utf8) and
+/// [ParseOptions::utf8_policy]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Replace invalid sequences with U+FFFD replacement character
+    #[default]
+    Replace,
+    /// Keep invalid sequences percent-encoded, so `%FF` stays `%FF` and no
+    /// information is lost
+    KeepEscaped,
+    /// Fail with [UrlSearchParamsError::InvalidUtf8]
+    Error,
+}
+
+/// Options of [try_parse_url_search_params](crate::try_parse_url_search_params)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail with [UrlSearchParamsError::DuplicateKey](crate::UrlSearchParamsError::DuplicateKey)
+    /// if a key appears more than once, off by default, so the last value wins
+    pub reject_duplicates: bool,
+    /// Pairs of alias and canonical name, decoded keys matching an alias
+    /// are renamed to the canonical name, empty by default
+    ///
+    /// Renaming happens before duplicates are checked, so `q=a&query=b`
+    /// with alias `q` of `query` has duplicate key `query`.
+    pub aliases: Vec<(String, String)>,
+    /// Decode plus sign `+` in keys and values as whitespace, as in
+    /// `application/x-www-form-urlencoded` strings, off by default
+    ///
+    /// Encoded plus sign `%2B` is decoded as `+` either way.
+    pub plus_as_space: bool,
+    /// Fail with [UrlSearchParamsError::MalformedPair](crate::UrlSearchParamsError::MalformedPair)
+    /// on a pair with empty key, such as `=value`, off by default, so such
+    /// pairs are skipped
+    ///
+    /// Empty pairs, as in `a=1&&b=2`, are skipped either way.
+    pub reject_empty_keys: bool,
+    /// Fail with [UrlSearchParamsError::LimitExceeded](crate::UrlSearchParamsError::LimitExceeded)
+    /// if the input is longer than the given number of bytes, no limit by default
+    pub max_len: Option<usize>,
+    /// Fail with [UrlSearchParamsError::LimitExceeded](crate::UrlSearchParamsError::LimitExceeded)
+    /// if there are more pairs than the given number, no limit by default
+    ///
+    /// Pairs with empty keys are counted too, even though they are skipped,
+    /// only empty pairs, as in `a=1&&b=2`, are not.
+    pub max_pairs: Option<usize>,
+    /// Fail with [UrlSearchParamsError::LimitExceeded](crate::UrlSearchParamsError::LimitExceeded)
+    /// if a key, before decoding, is longer than the given number of bytes,
+    /// no limit by default
+    pub max_key_len: Option<usize>,
+    /// Fail with [UrlSearchParamsError::LimitExceeded](crate::UrlSearchParamsError::LimitExceeded)
+    /// if a value, before decoding, is longer than the given number of
+    /// bytes, no limit by default
+    pub max_value_len: Option<usize>,
+    /// How malformed percent-encoding is handled, [DecodeMode::Strict] by default
+    pub decode_mode: DecodeMode,
+    /// Characters separating key-value pairs, only `&` by default
+    ///
+    /// Add `;` to parse `a=1;b=2` as two pairs, as older specifications
+    /// and some servers do.
+    pub separators: Vec<char>,
+    /// Strip one leading `?`, so input taken along with the URL delimiter,
+    /// like `?a=1`, does not produce `?a` key, off by default
+    pub strip_question_mark: bool,
+    /// Treat HTML entities of ampersand `&amp;`, `&#38;` and `&#x26;` as
+    /// pair separators, for URLs scraped from HTML, off by default
+    ///
+    /// Zero-padded numeric entities, such as `&#038;`, are recognized too.
+    pub html_entities: bool,
+    /// How invalid UTF-8 is handled, by default it follows
+    /// [ParseOptions::decode_mode]: strict mode fails on it, lossy mode
+    /// replaces it
+    pub utf8_policy: Option<Utf8Policy>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            reject_duplicates: false,
+            aliases: vec![],
+            plus_as_space: false,
+            reject_empty_keys: false,
+            max_len: None,
+            max_pairs: None,
+            max_key_len: None,
+            max_value_len: None,
+            decode_mode: DecodeMode::default(),
+            separators: vec!['&'],
+            strip_question_mark: false,
+            html_entities: false,
+            utf8_policy: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Decode given key or value according to the options
+    pub(crate) fn decode(&self, component: &str) -> Result<String, UrlSearchParamsError> {
+        if self.plus_as_space {
+            self.decode_escapes(&component.replace('+', " "))
+        } else {
+            self.decode_escapes(component)
+        }
+    }
+
+    fn decode_escapes(&self, component: &str) -> Result<String, UrlSearchParamsError> {
+        let policy = match self.utf8_policy {
+            Some(policy) => policy,
+            None => return decode_uri_component_with(component, self.decode_mode),
+        };
+
+        if self.decode_mode == DecodeMode::Strict {
+            if let Some(index) = find_malformed_escape(component) {
+                return Err(UrlSearchParamsError::Decode { input: component.to_string(), index })
+            }
+        }
+        decode_uri_component_utf8(component, policy)
+    }
+
+    /// Rename given decoded key to its canonical name, if it is an alias
+    pub(crate) fn resolve_alias(&self, key: String) -> String {
+        match self.aliases.iter().find(|(alias, _)| *alias == key) {
+            Some((_, canonical)) => canonical.to_string(),
+            None => key,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::ffi::{OsStr, OsString};
+use crate::{encode_byte_into, percent_decode_into, ENCODE_TABLE, HEX_UPPER};
+
+/// Percent-encode given OS string, for example a file path, to be used as a
+/// query string key or value
+///
+/// On Unix raw bytes of the OS string are encoded, so non-UTF-8 names
+/// survive the round trip via [decode_os_string]. On other platforms it is
+/// a best effort: the OS string is converted to UTF-8 first, replacing
+/// invalid sequences with U+FFFD replacement character.
+///
+/// # Examples
+///
+/// ```
+/// use std::ffi::OsStr;
+/// use url_search_params::encode_os_str;
+///
+/// assert_eq!(encode_os_str(OsStr::new("/tmp/my file.txt")), "%2Ftmp%2Fmy%20file.txt");
+/// ```
+pub fn encode_os_str(s: &OsStr) -> String {
+    let mut result = String::with_capacity(s.len());
+    for byte in os_str_bytes(s).iter() {
+        encode_byte_into(*byte, &ENCODE_TABLE, HEX_UPPER, &mut result);
+    }
+    result
+}
+
+/// Decode percent-encoded string into an OS string
+///
+/// On Unix decoded bytes are used as is, even if they are not valid UTF-8.
+/// On other platforms invalid UTF-8 sequences are replaced with U+FFFD
+/// replacement character.
+///
+/// # Examples
+///
+/// ```
+/// use std::ffi::OsString;
+/// use url_search_params::decode_os_string;
+///
+/// assert_eq!(decode_os_string("%2Ftmp%2Fmy%20file.txt"), OsString::from("/tmp/my file.txt"));
+/// ```
+pub fn decode_os_string(component: &str) -> OsString {
+    let mut bytes : Vec<u8> = Vec::with_capacity(component.len());
+    percent_decode_into(component.as_bytes(), &mut bytes);
+    os_string_from_bytes(bytes)
+}
+
+#[cfg(unix)]
+fn os_str_bytes(s: &OsStr) -> std::borrow::Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    std::borrow::Cow::Borrowed(s.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn os_str_bytes(s: &OsStr) -> std::borrow::Cow<'_, [u8]> {
+    match s.to_string_lossy() {
+        std::borrow::Cow::Borrowed(borrowed) => std::borrow::Cow::Borrowed(borrowed.as_bytes()),
+        std::borrow::Cow::Owned(owned) => std::borrow::Cow::Owned(owned.into_bytes()),
+    }
+}
+
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+    use crate::{decode_os_string, encode_os_str, encode_uri_component};
+
+    #[test]
+    fn encode_decode_utf8_os_str() {
+        let path = OsStr::new("/home/user/документи/звіт 1.txt");
+        let encoded = encode_os_str(path);
+        assert_eq!(encode_uri_component("/home/user/документи/звіт 1.txt"), encoded);
+        assert_eq!(path, decode_os_string(&encoded));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn encode_decode_non_utf8_os_str() {
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        let path = OsStr::from_bytes(b"/tmp/file\xFF\xFE.txt");
+        let encoded = encode_os_str(path);
+        assert_eq!("%2Ftmp%2Ffile%FF%FE.txt", encoded);
+
+        let decoded: OsString = decode_os_string(&encoded);
+        assert_eq!(b"/tmp/file\xFF\xFE.txt".to_vec(), decoded.into_vec());
+    }
+}
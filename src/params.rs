@@ -0,0 +1,1343 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::str::FromStr;
+use crate::{raw_optional_pairs, try_decode_uri_component, EncodeSet, QueryDiff, UrlSearchParamsError};
+use crate::diff::diff_maps;
+use crate::encode_set::encode_uri_component_with_into;
+
+/// List of decoded query string parameters, keeping their order and
+/// duplicate keys
+///
+/// Flags, parameters without `=` such as `debug` in `debug&page=2`, are
+/// kept as such, their value reads as empty string.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::UrlSearchParams;
+///
+/// let params = UrlSearchParams::try_from("key=value&another_key=its%20value").unwrap();
+///
+/// assert_eq!(2, params.len());
+/// assert_eq!(params.get("another_key"), Some("its value"));
+///
+/// let params = UrlSearchParams::try_from("debug&page=2").unwrap();
+/// assert_eq!(params.get("debug"), Some(""));
+/// assert_eq!(params.to_string(), "debug&page=2");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UrlSearchParams {
+    pairs: Vec<(String, Option<String>)>,
+    encoding: EncodeSet,
+}
+
+impl UrlSearchParams {
+    /// Create empty list of parameters
+    pub fn new() -> UrlSearchParams {
+        UrlSearchParams { pairs: vec![], encoding: EncodeSet::COMPONENT }
+    }
+
+    /// Create empty list of parameters with room for at least `capacity`
+    /// parameters without reallocating
+    pub fn with_capacity(capacity: usize) -> UrlSearchParams {
+        UrlSearchParams::from_pairs(Vec::with_capacity(capacity))
+    }
+
+    /// Reserve room for at least `additional` more parameters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::with_capacity(4);
+    /// assert!(params.capacity() >= 4);
+    ///
+    /// params.reserve(16);
+    /// assert!(params.capacity() >= 16);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.pairs.reserve(additional);
+    }
+
+    /// Number of parameters the list can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.pairs.capacity()
+    }
+
+    fn from_pairs(pairs: Vec<(String, Option<String>)>) -> UrlSearchParams {
+        UrlSearchParams { pairs, encoding: EncodeSet::COMPONENT }
+    }
+
+    /// Pairs sorted by key and then by value, which is the order semantic
+    /// comparison and hashing rely on
+    fn sorted_pairs(&self) -> Vec<&(String, Option<String>)> {
+        let mut pairs : Vec<&(String, Option<String>)> = self.pairs.iter().collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Set encode set used by `to_string`, [EncodeSet::COMPONENT] by default
+    ///
+    /// See [EncodeSet] on which one to pick.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::{EncodeSet, UrlSearchParams};
+    ///
+    /// let mut params = UrlSearchParams::try_from("q=a%20b").unwrap();
+    /// assert_eq!(params.to_string(), "q=a%20b");
+    ///
+    /// params.set_encoding(EncodeSet::FORM);
+    /// assert_eq!(params.to_string(), "q=a+b");
+    /// ```
+    pub fn set_encoding(&mut self, set: EncodeSet) {
+        self.encoding = set;
+    }
+
+    /// Convert parameters into a query string using the given encode set,
+    /// regardless of the one set via [UrlSearchParams::set_encoding]
+    pub fn to_string_with(&self, set: &EncodeSet) -> String {
+        let mut url_search_params = String::new();
+        for (index, (key, value)) in self.pairs.iter().enumerate() {
+            if index > 0 {
+                url_search_params.push('&');
+            }
+            encode_uri_component_with_into(key, set, &mut url_search_params);
+            if let Some(value) = value {
+                url_search_params.push('=');
+                encode_uri_component_with_into(value, set, &mut url_search_params);
+            }
+        }
+        url_search_params
+    }
+
+    /// Number of parameters, including duplicate keys
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Add parameter to the end, keeping existing parameters with the same key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::new();
+    /// params.append("tag", "a b");
+    /// params.append("tag", "c");
+    ///
+    /// assert_eq!(params.to_string(), "tag=a%20b&tag=c");
+    /// ```
+    pub fn append(&mut self, key: &str, value: &str) {
+        self.pairs.push((key.to_string(), Some(value.to_string())));
+    }
+
+    /// Add flag, parameter without value, to the end
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::new();
+    /// params.append_flag("debug");
+    /// params.append("page", "2");
+    ///
+    /// assert_eq!(params.to_string(), "debug&page=2");
+    /// assert!(params.is_flag("debug"));
+    /// ```
+    pub fn append_flag(&mut self, key: &str) {
+        self.pairs.push((key.to_string(), None));
+    }
+
+    /// Check if the first parameter with the given key is a flag, without
+    /// value
+    pub fn is_flag(&self, key: &str) -> bool {
+        self.pairs.iter()
+            .find(|(pair_key, _)| pair_key == key)
+            .is_some_and(|(_, value)| value.is_none())
+    }
+
+    /// Append parameter only if the value is present, formatting it via
+    /// `Display`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::new();
+    /// params.append_if_some("limit", Some(10));
+    /// params.append_if_some("cursor", None::<String>);
+    ///
+    /// assert_eq!(params.to_string(), "limit=10");
+    /// ```
+    pub fn append_if_some<T: fmt::Display>(&mut self, key: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.append(key, &value.to_string());
+        }
+    }
+
+    /// Append parameter only if the condition holds
+    pub fn append_if(&mut self, condition: bool, key: &str, value: &str) {
+        if condition {
+            self.append(key, value);
+        }
+    }
+
+    /// Get the first value of the given key, empty one for a flag
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs.iter()
+            .find(|(pair_key, _)| pair_key == key)
+            .map(|(_, value)| value.as_deref().unwrap_or_default())
+    }
+
+    /// Get the first value of the given key or the default if there is none
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let params = UrlSearchParams::try_from("page=3").unwrap();
+    ///
+    /// assert_eq!(params.get_or("page", "1"), "3");
+    /// assert_eq!(params.get_or("per_page", "20"), "20");
+    /// ```
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Append defaults for keys which are not present, sorted by key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut defaults: HashMap<String, String> = HashMap::new();
+    /// defaults.insert("page".to_string(), "1".to_string());
+    /// defaults.insert("per_page".to_string(), "20".to_string());
+    ///
+    /// let params = UrlSearchParams::try_from("page=3").unwrap().with_defaults(defaults);
+    /// assert_eq!(params.to_string(), "page=3&per_page=20");
+    /// ```
+    pub fn with_defaults<S: BuildHasher>(mut self, defaults: HashMap<String, String, S>) -> UrlSearchParams {
+        self.merge(UrlSearchParams::from(defaults), MergeStrategy::KeepExisting);
+        self
+    }
+
+    /// Get the first value of the given key, same as [UrlSearchParams::get],
+    /// matching frameworks where the first value wins, like Express
+    pub fn get_first(&self, key: &str) -> Option<&str> {
+        self.get(key)
+    }
+
+    /// Get the last value of the given key, matching frameworks where the
+    /// last value wins, like PHP
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let params = UrlSearchParams::try_from("sort=asc&sort=desc").unwrap();
+    ///
+    /// assert_eq!(params.get_first("sort"), Some("asc"));
+    /// assert_eq!(params.get_last("sort"), Some("desc"));
+    /// ```
+    pub fn get_last(&self, key: &str) -> Option<&str> {
+        self.pairs.iter()
+            .rfind(|(pair_key, _)| pair_key == key)
+            .map(|(_, value)| value.as_deref().unwrap_or_default())
+    }
+
+    /// Get all values of the given key, in the order they were added
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let params = UrlSearchParams::try_from("tag=a&id=1&tag=b").unwrap();
+    ///
+    /// assert_eq!(params.get_all("tag"), vec!["a", "b"]);
+    /// assert!(params.get_all("missing").is_empty());
+    /// ```
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.pairs.iter()
+            .filter(|(pair_key, _)| pair_key == key)
+            .map(|(_, value)| value.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    /// Check if there is at least one parameter with the given key
+    pub fn has(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(pair_key, _)| pair_key == key)
+    }
+
+    /// Set value of the given key
+    ///
+    /// Value of the first parameter with the given key is replaced and the
+    /// rest of parameters with that key are removed. If there is no such
+    /// parameter, it is added to the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("tag=a&id=1&tag=b").unwrap();
+    /// params.set("tag", "c");
+    /// params.set("page", "2");
+    ///
+    /// assert_eq!(params.to_string(), "tag=c&id=1&page=2");
+    /// ```
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.pairs.iter().position(|(pair_key, _)| pair_key == key) {
+            Some(index) => {
+                self.pairs[index].1 = Some(value.to_string());
+                let mut position = 0;
+                self.pairs.retain(|(pair_key, _)| {
+                    let keep = position <= index || pair_key != key;
+                    position += 1;
+                    keep
+                });
+            }
+            None => self.append(key, value),
+        }
+    }
+
+    /// Get entry of the given key for in-place manipulation
+    ///
+    /// Entry refers to the first parameter with the given key, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("page=1").unwrap();
+    /// params.entry("page").and_modify(|page| page.push('0')).or_insert("1");
+    /// params.entry("limit").or_insert("20");
+    ///
+    /// assert_eq!(params.to_string(), "page=10&limit=20");
+    /// ```
+    pub fn entry(&mut self, key: &str) -> ParamEntry<'_> {
+        let index = self.pairs.iter().position(|(pair_key, _)| pair_key == key);
+        ParamEntry { pairs: &mut self.pairs, key: key.to_string(), index }
+    }
+
+    /// Rename all parameters with the given key, keeping their values and
+    /// positions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("q=rust&page=2").unwrap();
+    /// params.rename_key("q", "query");
+    ///
+    /// assert_eq!(params.to_string(), "query=rust&page=2");
+    /// ```
+    pub fn rename_key(&mut self, old: &str, new: &str) {
+        for (key, _) in self.pairs.iter_mut().filter(|(key, _)| key == old) {
+            *key = new.to_string();
+        }
+    }
+
+    /// Merge other parameters into these ones, resolving keys present in
+    /// both according to the strategy
+    ///
+    /// Parameters of the other list are appended in their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::{MergeStrategy, UrlSearchParams};
+    ///
+    /// let defaults = UrlSearchParams::try_from("limit=20&format=json").unwrap();
+    /// let mut params = UrlSearchParams::try_from("limit=50").unwrap();
+    /// params.merge(defaults, MergeStrategy::KeepExisting);
+    ///
+    /// assert_eq!(params.to_string(), "limit=50&format=json");
+    /// ```
+    pub fn merge(&mut self, other: UrlSearchParams, strategy: MergeStrategy) {
+        match strategy {
+            MergeStrategy::Overwrite => {
+                for (key, _) in other.pairs.iter() {
+                    self.delete(key);
+                }
+                self.pairs.extend(other.pairs);
+            }
+            MergeStrategy::KeepExisting => {
+                let existing : Vec<String> = self.pairs.iter().map(|(key, _)| key.to_string()).collect();
+                self.pairs.extend(other.pairs.into_iter().filter(|(key, _)| !existing.contains(key)));
+            }
+            MergeStrategy::AppendDuplicate => self.pairs.extend(other.pairs),
+        }
+    }
+
+    /// Get parameters whose keys start with the given prefix, with the
+    /// prefix stripped
+    ///
+    /// If the prefix ends with `[`, the `]` closing it is stripped as well, so
+    /// `filter[price]` with prefix `filter[` becomes `price` and
+    /// `filter[tags][]` becomes `tags[]`, keys without the closing `]` are
+    /// skipped. Parameters whose key is empty after stripping are skipped.
+    /// Encode set is carried over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let params = UrlSearchParams::try_from("filter%5Bprice%5D=10&filter%5Bcolor%5D=red&page=2").unwrap();
+    /// assert_eq!(params.sub_params("filter[").to_string(), "price=10&color=red");
+    ///
+    /// let params = UrlSearchParams::try_from("filter.price=10&page=2").unwrap();
+    /// assert_eq!(params.sub_params("filter.").to_string(), "price=10");
+    /// ```
+    pub fn sub_params(&self, prefix: &str) -> UrlSearchParams {
+        let is_bracketed = prefix.ends_with('[');
+
+        let pairs : Vec<(String, Option<String>)> = self.pairs.iter()
+            .filter_map(|(key, value)| {
+                let mut key = key.strip_prefix(prefix)?.to_string();
+                if is_bracketed {
+                    let (name, rest) = key.split_once(']')?;
+                    key = [name, rest].join("");
+                }
+                if key.is_empty() {
+                    None
+                } else {
+                    Some((key, value.clone()))
+                }
+            })
+            .collect();
+        UrlSearchParams { pairs, encoding: self.encoding }
+    }
+
+    /// Check if every parameter of the other list, key and value, is present
+    /// in this one
+    ///
+    /// Order and number of occurrences are not taken into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let request = UrlSearchParams::try_from("q=rust&page=2&sig=abc").unwrap();
+    /// let expected = UrlSearchParams::try_from("page=2&q=rust").unwrap();
+    ///
+    /// assert!(request.contains_all(&expected));
+    /// assert!(expected.is_subset(&request));
+    /// ```
+    pub fn contains_all(&self, other: &UrlSearchParams) -> bool {
+        other.pairs.iter().all(|pair| self.pairs.contains(pair))
+    }
+
+    /// Check if every parameter of this list is present in the other one,
+    /// same as `other.contains_all(self)`
+    pub fn is_subset(&self, other: &UrlSearchParams) -> bool {
+        other.contains_all(self)
+    }
+
+    /// Compare these parameters, as the old ones, with the new ones
+    ///
+    /// Same as [diff](crate::diff), for duplicate keys the last value is
+    /// compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let old = UrlSearchParams::try_from("q=rust&page=1").unwrap();
+    /// let new = UrlSearchParams::try_from("q=rust&page=2&lang=en").unwrap();
+    ///
+    /// let query_diff = old.diff(&new);
+    /// assert_eq!(query_diff.added, vec![("lang".to_string(), "en".to_string())]);
+    /// assert_eq!(query_diff.changed[0].key, "page");
+    /// assert!(query_diff.removed.is_empty());
+    /// ```
+    pub fn diff(&self, new: &UrlSearchParams) -> QueryDiff {
+        diff_maps(&self.to_map(), &new.to_map())
+    }
+
+    /// Collapse parameters into a HashMap, the last value of a repeated
+    /// key wins
+    fn to_map(&self) -> HashMap<String, String> {
+        self.pairs.iter()
+            .map(|(key, value)| (key.clone(), value.clone().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Remove all parameters with the given key and value if there are any,
+    /// otherwise append the parameter
+    ///
+    /// Flags are matched by empty value. Returns `true` if parameter was
+    /// appended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let current = UrlSearchParams::try_from("color=red&color=blue").unwrap();
+    ///
+    /// let mut link = current.clone();
+    /// link.toggle("color", "red");
+    /// assert_eq!(link.to_string(), "color=blue");
+    ///
+    /// let mut link = current.clone();
+    /// link.toggle("color", "green");
+    /// assert_eq!(link.to_string(), "color=red&color=blue&color=green");
+    /// ```
+    pub fn toggle(&mut self, key: &str, value: &str) -> bool {
+        let len = self.pairs.len();
+        self.pairs.retain(|(pair_key, pair_value)| pair_key != key || pair_value.as_deref().unwrap_or_default() != value);
+
+        let is_absent = len == self.pairs.len();
+        if is_absent {
+            self.append(key, value);
+        }
+        is_absent
+    }
+
+    /// Remove all parameters with the given key
+    pub fn delete(&mut self, key: &str) {
+        self.pairs.retain(|(pair_key, _)| pair_key != key);
+    }
+
+    /// Sort parameters with the given comparator of key-value pairs, flags
+    /// are passed with empty value
+    ///
+    /// Sorting is stable, so parameters the comparator considers equal keep
+    /// their relative order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("b=1&C=2&a=3").unwrap();
+    /// params.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+    ///
+    /// assert_eq!(params.to_string(), "C=2&a=3&b=1");
+    /// ```
+    pub fn sort_by<F: FnMut((&str, &str), (&str, &str)) -> Ordering>(&mut self, mut compare: F) {
+        self.pairs.sort_by(|(a_key, a_value), (b_key, b_value)| {
+            compare((a_key, a_value.as_deref().unwrap_or_default()), (b_key, b_value.as_deref().unwrap_or_default()))
+        });
+    }
+
+    /// Keep only parameters for which the predicate returns `true`, in
+    /// their order, flags are passed with empty value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("q=rust&utm_source=x&page=2").unwrap();
+    /// params.retain(|key, _| ["q", "page"].contains(&key));
+    ///
+    /// assert_eq!(params.to_string(), "q=rust&page=2");
+    /// ```
+    pub fn retain<F: FnMut(&str, &str) -> bool>(&mut self, mut f: F) {
+        self.pairs.retain(|(key, value)| f(key, value.as_deref().unwrap_or_default()));
+    }
+
+    /// Replace value of every parameter with the one returned by the function
+    ///
+    /// Flags have no value, so they are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("token=secret&q=rust").unwrap();
+    /// params.map_values(|key, value| if key == "token" { "***".to_string() } else { value.to_string() });
+    ///
+    /// assert_eq!(params.to_string(), "token=%2A%2A%2A&q=rust");
+    /// ```
+    pub fn map_values<F: FnMut(&str, &str) -> String>(&mut self, mut f: F) {
+        for (key, value) in self.pairs.iter_mut() {
+            if let Some(value) = value {
+                *value = f(key, value);
+            }
+        }
+    }
+
+    /// Sort parameters by key
+    ///
+    /// Sorting is stable, so values of the same key keep their relative order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("c=1&a=2&c=0&b=3").unwrap();
+    /// params.sort();
+    ///
+    /// assert_eq!(params.to_string(), "a=2&b=3&c=1&c=0");
+    /// ```
+    pub fn sort(&mut self) {
+        self.pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+
+    /// Iterate over keys in order, a repeated key is yielded every time it
+    /// appears
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.pairs.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Iterate over values in order, empty ones for flags
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.pairs.iter().map(|(_, value)| value.as_deref().unwrap_or_default())
+    }
+
+    /// Iterate over key-value pairs in order, flags with empty value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let params = UrlSearchParams::try_from("b=1&a=2&b=3").unwrap();
+    ///
+    /// assert_eq!(params.keys().collect::<Vec<&str>>(), vec!["b", "a", "b"]);
+    /// assert_eq!(params.values().collect::<Vec<&str>>(), vec!["1", "2", "3"]);
+    /// assert_eq!(params.entries().next(), Some(("b", "1")));
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(entry_str)
+    }
+
+    /// Remove the last parameter and return it, or `None` if there are no
+    /// parameters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_search_params::UrlSearchParams;
+    ///
+    /// let mut params = UrlSearchParams::try_from("a=1&b=2").unwrap();
+    ///
+    /// assert_eq!(params.pop(), Some(("b".to_string(), "2".to_string())));
+    /// assert_eq!(params.pop(), Some(("a".to_string(), "1".to_string())));
+    /// assert_eq!(params.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<(String, String)> {
+        self.pairs.pop().map(entry_string)
+    }
+}
+
+fn entry_str((key, value): &(String, Option<String>)) -> (&str, &str) {
+    (key, value.as_deref().unwrap_or_default())
+}
+
+fn entry_string((key, value): (String, Option<String>)) -> (String, String) {
+    (key, value.unwrap_or_default())
+}
+
+/// How [UrlSearchParams::merge] resolves keys present in both parameter lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Values of the other list replace values of the key
+    Overwrite,
+    /// Values of the key are kept, ones from the other list are ignored
+    KeepExisting,
+    /// Values of the other list are appended as duplicates
+    AppendDuplicate,
+}
+
+/// Parameter of [UrlSearchParams], which may or may not be present, see
+/// [UrlSearchParams::entry]
+#[derive(Debug)]
+pub struct ParamEntry<'a> {
+    pairs: &'a mut Vec<(String, Option<String>)>,
+    key: String,
+    index: Option<usize>,
+}
+
+impl<'a> ParamEntry<'a> {
+    /// Key of the entry
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Check if parameter with the key is present
+    pub fn is_present(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Modify value of the parameter if it is present, a flag gets empty
+    /// value first
+    pub fn and_modify<F: FnOnce(&mut String)>(self, f: F) -> Self {
+        if let Some(index) = self.index {
+            f(self.pairs[index].1.get_or_insert_with(String::new));
+        }
+        self
+    }
+
+    /// Append parameter with the given value if it is not present, return
+    /// mutable reference to the value
+    pub fn or_insert(self, value: &str) -> &'a mut String {
+        self.or_insert_with(|| value.to_string())
+    }
+
+    /// Append parameter with the value returned by the function if it is not
+    /// present, return mutable reference to the value
+    ///
+    /// A present flag gets empty value.
+    pub fn or_insert_with<F: FnOnce() -> String>(self, f: F) -> &'a mut String {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                self.pairs.push((self.key, Some(f())));
+                self.pairs.len() - 1
+            }
+        };
+        self.pairs[index].1.get_or_insert_with(String::new)
+    }
+
+    /// Append parameter with empty value if it is not present, return
+    /// mutable reference to the value
+    pub fn or_default(self) -> &'a mut String {
+        self.or_insert_with(String::new)
+    }
+}
+
+/// Convert parameters into a query string, in the order they were added,
+/// using encode set set via [UrlSearchParams::set_encoding]
+impl fmt::Display for UrlSearchParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(&self.encoding))
+    }
+}
+
+/// Collapse given HashMap into parameters, sorted by key
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use url_search_params::UrlSearchParams;
+///
+/// let mut params_map: HashMap<String, String> = HashMap::new();
+/// params_map.insert("key".to_string(), "value".to_string());
+///
+/// let params = UrlSearchParams::from(params_map);
+/// assert_eq!(params.get("key"), Some("value"));
+/// ```
+impl<S: BuildHasher> From<HashMap<String, String, S>> for UrlSearchParams {
+    fn from(params: HashMap<String, String, S>) -> Self {
+        let mut pairs : Vec<(String, Option<String>)> = params.into_iter()
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        pairs.sort();
+        UrlSearchParams::from_pairs(pairs)
+    }
+}
+
+/// Parse given query string, failing on malformed percent-encoding or
+/// invalid UTF-8, see [try_decode_uri_component]
+///
+/// Parameters without `=` are kept as flags.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{UrlSearchParams, UrlSearchParamsError};
+///
+/// let params = UrlSearchParams::try_from("a=1&b=%20").unwrap();
+/// assert_eq!(params.get("b"), Some(" "));
+///
+/// let boxed_params = UrlSearchParams::try_from("a=100%");
+/// assert!(matches!(boxed_params, Err(UrlSearchParamsError::Decode { .. })));
+/// ```
+impl TryFrom<&str> for UrlSearchParams {
+    type Error = UrlSearchParamsError;
+
+    fn try_from(params: &str) -> Result<Self, Self::Error> {
+        let mut pairs : Vec<(String, Option<String>)> = vec![];
+        for (key, value) in raw_optional_pairs(params) {
+            let value = match value {
+                Some(value) => Some(try_decode_uri_component(value)?),
+                None => None,
+            };
+            pairs.push((try_decode_uri_component(key)?, value));
+        }
+        Ok(UrlSearchParams::from_pairs(pairs))
+    }
+}
+
+/// Parse given query string, same as [UrlSearchParams::try_from] does
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{UrlSearchParams, UrlSearchParamsError};
+///
+/// fn main() -> Result<(), UrlSearchParamsError> {
+///     let params: UrlSearchParams = "q=rust%20url&page=2".parse()?;
+///     assert_eq!(params.get("q"), Some("rust url"));
+///     assert_eq!(format!("{params}"), "q=rust%20url&page=2");
+///     Ok(())
+/// }
+/// ```
+impl FromStr for UrlSearchParams {
+    type Err = UrlSearchParamsError;
+
+    fn from_str(params: &str) -> Result<Self, Self::Err> {
+        UrlSearchParams::try_from(params)
+    }
+}
+
+/// Compare parameters semantically
+///
+/// Parameters are compared decoded and regardless of their order, so
+/// `b=2&a=1` equals `a=1&b=%32`. Duplicate keys count, `a=1&a=1` is not
+/// equal to `a=1`, and so do flags, `a` is not equal to `a=`. Encode set
+/// is not taken into account.
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::UrlSearchParams;
+///
+/// let a = UrlSearchParams::try_from("b=2&a=%7E").unwrap();
+/// let b = UrlSearchParams::try_from("a=~&b=2").unwrap();
+/// assert_eq!(a, b);
+/// ```
+impl PartialEq for UrlSearchParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs.len() == other.pairs.len() && self.sorted_pairs() == other.sorted_pairs()
+    }
+}
+
+impl Eq for UrlSearchParams {}
+
+/// Hash parameters consistently with the semantic [PartialEq]
+impl Hash for UrlSearchParams {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted_pairs().hash(state);
+    }
+}
+
+/// Collect parameters from key-value pairs, keeping their order
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::UrlSearchParams;
+///
+/// let params = UrlSearchParams::try_from("utm_source=x&id=1&utm_medium=y").unwrap();
+/// let params: UrlSearchParams = params.into_iter()
+///     .filter(|(key, _)| !key.starts_with("utm_"))
+///     .collect();
+///
+/// assert_eq!(params.to_string(), "id=1");
+/// ```
+impl FromIterator<(String, String)> for UrlSearchParams {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        UrlSearchParams::from_pairs(iter.into_iter().map(|(key, value)| (key, Some(value))).collect())
+    }
+}
+
+/// Append key-value pairs to the end
+impl Extend<(String, String)> for UrlSearchParams {
+    fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, iter: I) {
+        self.pairs.extend(iter.into_iter().map(|(key, value)| (key, Some(value))));
+    }
+}
+
+/// Iterate over key-value pairs in order, flags with empty value
+impl IntoIterator for UrlSearchParams {
+    type Item = (String, String);
+    type IntoIter = std::iter::Map<std::vec::IntoIter<(String, Option<String>)>, fn((String, Option<String>)) -> (String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pairs.into_iter().map(entry_string)
+    }
+}
+
+/// Iterate over key-value pairs in order, same as [UrlSearchParams::entries]
+impl<'a> IntoIterator for &'a UrlSearchParams {
+    type Item = (&'a str, &'a str);
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (String, Option<String>)>, fn(&'a (String, Option<String>)) -> (&'a str, &'a str)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pairs.iter().map(entry_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use crate::{EncodeSet, MergeStrategy, UrlSearchParams, UrlSearchParamsError};
+
+    #[test]
+    fn from_hash_map() {
+        let mut params_map: HashMap<String, String> = HashMap::new();
+        params_map.insert("b".to_string(), "2".to_string());
+        params_map.insert("a".to_string(), "1".to_string());
+
+        let params = UrlSearchParams::from(params_map);
+        assert_eq!(2, params.len());
+        assert_eq!(vec![("a", "1"), ("b", "2")], params.entries().collect::<Vec<(&str, &str)>>());
+    }
+
+    #[test]
+    fn try_from_str() {
+        let params = UrlSearchParams::try_from("b=2&a=1&b=3&=x").unwrap();
+        assert_eq!(3, params.len());
+        assert_eq!(Some("2"), params.get("b"));
+        assert_eq!(Some("1"), params.get("a"));
+        assert!(params.get("c").is_none());
+
+        let params = UrlSearchParams::try_from("").unwrap();
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn try_from_malformed_str() {
+        let boxed_params = UrlSearchParams::try_from("a=1&b=%ZZ");
+        let error = UrlSearchParamsError::Decode { input: "%ZZ".to_string(), index: 0 };
+        assert_eq!(error, boxed_params.unwrap_err());
+
+        let boxed_params = UrlSearchParams::try_from("a%FF=1");
+        let error = UrlSearchParamsError::InvalidUtf8("a%FF".to_string());
+        assert_eq!(error, boxed_params.unwrap_err());
+    }
+
+    #[test]
+    fn pop() {
+        let mut params = UrlSearchParams::try_from("a=1&b=2&a=3").unwrap();
+
+        assert_eq!(Some(("a".to_string(), "3".to_string())), params.pop());
+        assert_eq!(vec![("a", "1"), ("b", "2")], params.entries().collect::<Vec<(&str, &str)>>());
+
+        assert!(params.pop().is_some());
+        assert!(params.pop().is_some());
+        assert!(params.pop().is_none());
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        let params = UrlSearchParams::try_from("debug&x=1&empty=&debug").unwrap();
+        assert_eq!(4, params.len());
+        assert_eq!("debug&x=1&empty=&debug", params.to_string());
+
+        let boxed_get = params.get("debug");
+        assert!(boxed_get.is_some());
+
+        let actual_param_value = boxed_get.unwrap();
+        assert_eq!(actual_param_value, "");
+
+        assert!(params.is_flag("debug"));
+        assert!(!params.is_flag("empty"));
+        assert!(!params.is_flag("missing"));
+        assert_ne!(params, UrlSearchParams::try_from("debug=&x=1&empty=&debug=").unwrap());
+
+        let mut built = UrlSearchParams::new();
+        built.append_flag("debug");
+        built.append("x", "1");
+        built.append("empty", "");
+        built.append_flag("debug");
+        assert_eq!(params, built);
+        assert_eq!(params.to_string(), built.to_string());
+    }
+
+    #[test]
+    fn flags_modified() {
+        let mut params = UrlSearchParams::try_from("a&b&c").unwrap();
+
+        params.map_values(|_, _| "mapped".to_string());
+        assert_eq!("a&b&c", params.to_string());
+
+        params.set("a", "1");
+        params.entry("b").and_modify(|value| value.push('2'));
+        assert!(params.toggle("x", ""));
+        assert!(!params.toggle("c", ""));
+        assert_eq!("a=1&b=2&x=", params.to_string());
+
+        let mut params = UrlSearchParams::try_from("f%5Bdebug%5D&f%5Bx%5D=1").unwrap();
+        assert_eq!("debug&x=1", params.sub_params("f[").to_string());
+        assert_eq!(Some(("f[x]".to_string(), "1".to_string())), params.pop());
+        assert_eq!(Some(("f[debug]".to_string(), "".to_string())), params.pop());
+    }
+
+    #[test]
+    fn to_string_encoding() {
+        let mut params = UrlSearchParams::try_from("b=a%20b&a=1%2B1").unwrap();
+        assert_eq!("b=a%20b&a=1%2B1", params.to_string());
+        assert_eq!("b=a+b&a=1%2B1", params.to_string_with(&EncodeSet::FORM));
+
+        params.set_encoding(EncodeSet::FORM);
+        assert_eq!("b=a+b&a=1%2B1", params.to_string());
+        assert_eq!("b=a%20b&a=1%2B1", params.to_string_with(&EncodeSet::COMPONENT));
+
+        assert_eq!("", UrlSearchParams::new().to_string());
+    }
+
+    #[test]
+    fn whatwg_api() {
+        let mut params = UrlSearchParams::new();
+        params.append("q", "rust & url");
+        params.append("tag", "a");
+        params.append("tag", "b");
+        params.append("page", "1");
+
+        assert_eq!("q=rust%20%26%20url&tag=a&tag=b&page=1", params.to_string());
+        assert_eq!(Some("rust & url"), params.get("q"));
+        assert_eq!(vec!["a", "b"], params.get_all("tag"));
+        assert!(params.has("page"));
+        assert!(!params.has("missing"));
+
+        params.set("tag", "c");
+        assert_eq!(vec!["c"], params.get_all("tag"));
+        assert_eq!("q=rust%20%26%20url&tag=c&page=1", params.to_string());
+
+        params.set("sort", "asc");
+        assert_eq!(Some("asc"), params.get("sort"));
+
+        params.delete("q");
+        assert!(!params.has("q"));
+        params.delete("missing");
+
+        params.append("a", "2");
+        params.append("a", "1");
+        params.sort();
+        assert_eq!("a=2&a=1&page=1&sort=asc&tag=c", params.to_string());
+    }
+
+    #[test]
+    fn set_removes_only_later_duplicates() {
+        let mut params = UrlSearchParams::try_from("x=0&a=1&b=2&a=3&c=4&a=5").unwrap();
+        params.set("a", "new");
+
+        let expected = vec![("x", "0"), ("a", "new"), ("b", "2"), ("c", "4")];
+        assert_eq!(expected, params.entries().collect::<Vec<(&str, &str)>>());
+    }
+
+    #[test]
+    fn entry() {
+        let mut params = UrlSearchParams::try_from("a=1&b=2&a=3").unwrap();
+
+        let entry = params.entry("a");
+        assert_eq!("a", entry.key());
+        assert!(entry.is_present());
+        *entry.or_insert("unused") += "0";
+        assert_eq!(vec!["10", "3"], params.get_all("a"));
+
+        let entry = params.entry("c");
+        assert!(!entry.is_present());
+        entry.and_modify(|value| value.push_str("unused")).or_insert("new");
+        assert_eq!(Some("new"), params.get("c"));
+
+        params.entry("d").or_default().push('x');
+        params.entry("b").and_modify(|value| *value = "two".to_string());
+        params.entry("e").or_insert_with(|| "lazy".to_string());
+
+        assert_eq!("a=10&b=two&a=3&c=new&d=x&e=lazy", params.to_string());
+    }
+
+    #[test]
+    fn append_optional() {
+        let mut params = UrlSearchParams::new();
+        params.append_if_some("limit", Some(20));
+        params.append_if_some("since", None::<u64>);
+        params.append_if(false, "debug", "1");
+        params.append_if(true, "verbose", "1");
+
+        assert_eq!(vec![("limit", "20"), ("verbose", "1")], params.entries().collect::<Vec<(&str, &str)>>());
+    }
+
+    #[test]
+    fn from_str_display_round_trip() {
+        let query = "b=a%20b&a=1%2B1&b=%D0%BA";
+        let params: UrlSearchParams = query.parse().unwrap();
+        assert_eq!(3, params.len());
+        assert_eq!(query, format!("{params}"));
+
+        let reparsed: UrlSearchParams = params.to_string().parse().unwrap();
+        assert_eq!(params.pairs, reparsed.pairs);
+
+        let boxed_params = "a=%".parse::<UrlSearchParams>();
+        assert!(boxed_params.is_err());
+    }
+
+    #[test]
+    fn iterators() {
+        let params: UrlSearchParams = vec![
+            ("b".to_string(), "2".to_string()),
+            ("a".to_string(), "1".to_string()),
+        ].into_iter().collect();
+        assert_eq!("b=2&a=1", params.to_string());
+
+        let mut keys: Vec<&str> = vec![];
+        for (key, _) in &params {
+            keys.push(key);
+        }
+        assert_eq!(vec!["b", "a"], keys);
+
+        let mut params: UrlSearchParams = params.into_iter()
+            .map(|(key, value)| (key.to_uppercase(), value))
+            .collect();
+        params.extend(vec![("c".to_string(), "3 4".to_string())]);
+        assert_eq!("B=2&A=1&c=3%204", params.to_string());
+
+        let pairs: Vec<(String, String)> = params.into_iter().collect();
+        assert_eq!(3, pairs.len());
+    }
+
+    #[test]
+    fn semantic_equality() {
+        let a = UrlSearchParams::try_from("b=2&a=1&c=%7E").unwrap();
+        let b = UrlSearchParams::try_from("a=1&c=~&b=%32").unwrap();
+        assert_eq!(a, b);
+
+        let mut c = b.clone();
+        c.set_encoding(EncodeSet::FORM);
+        assert_eq!(a, c);
+
+        assert_ne!(a, UrlSearchParams::try_from("a=1&b=2").unwrap());
+        assert_ne!(UrlSearchParams::try_from("a=1&a=1").unwrap(), UrlSearchParams::try_from("a=1").unwrap());
+        assert_ne!(UrlSearchParams::try_from("a=1&a=2").unwrap(), UrlSearchParams::try_from("a=1&a=1").unwrap());
+        assert_eq!(UrlSearchParams::new(), UrlSearchParams::try_from("").unwrap());
+    }
+
+    #[test]
+    fn semantic_hash() {
+        let mut crawled : HashSet<UrlSearchParams> = HashSet::new();
+        crawled.insert(UrlSearchParams::try_from("page=2&sort=asc").unwrap());
+        crawled.insert(UrlSearchParams::try_from("sort=asc&page=%32").unwrap());
+        crawled.insert(UrlSearchParams::try_from("sort=desc&page=2").unwrap());
+
+        assert_eq!(2, crawled.len());
+    }
+
+    #[test]
+    fn retain_and_map_values() {
+        let mut params = UrlSearchParams::try_from("a=1&utm_source=x&b=2&a=3&utm_medium=y").unwrap();
+
+        params.retain(|key, value| !key.starts_with("utm_") && value != "3");
+        assert_eq!("a=1&b=2", params.to_string());
+
+        params.map_values(|key, value| [key, value].join(":"));
+        assert_eq!(vec!["a:1"], params.get_all("a"));
+        assert_eq!(Some("b:2"), params.get("b"));
+
+        params.retain(|_, _| false);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn rename_key() {
+        let mut params = UrlSearchParams::try_from("q=a&page=1&q=b").unwrap();
+
+        params.rename_key("q", "query");
+        assert_eq!("query=a&page=1&query=b", params.to_string());
+
+        params.rename_key("missing", "x");
+        params.rename_key("page", "query");
+        assert_eq!(vec!["a", "1", "b"], params.get_all("query"));
+    }
+
+    #[test]
+    fn get_first_and_last() {
+        let params = UrlSearchParams::try_from("a=1&b=2&a=3&a=").unwrap();
+
+        assert_eq!(Some("1"), params.get_first("a"));
+        assert_eq!(Some(""), params.get_last("a"));
+        assert_eq!(Some("2"), params.get_first("b"));
+        assert_eq!(Some("2"), params.get_last("b"));
+        assert!(params.get_first("c").is_none());
+        assert!(params.get_last("c").is_none());
+    }
+
+    #[test]
+    fn keys_values_entries() {
+        let params = UrlSearchParams::try_from("z=1&a=%20&z=3").unwrap();
+
+        assert_eq!(vec!["z", "a", "z"], params.keys().collect::<Vec<&str>>());
+        assert_eq!(vec!["1", " ", "3"], params.values().collect::<Vec<&str>>());
+        assert_eq!(vec![("z", "1"), ("a", " "), ("z", "3")], params.entries().collect::<Vec<(&str, &str)>>());
+
+        let params = UrlSearchParams::new();
+        assert_eq!(0, params.keys().count());
+        assert_eq!(0, params.values().count());
+        assert_eq!(0, params.entries().count());
+    }
+
+    #[test]
+    fn sort_by() {
+        let mut params = UrlSearchParams::try_from("b=1&C=2&a=3&b=0").unwrap();
+
+        params.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!("C=2&a=3&b=1&b=0", params.to_string());
+
+        params.sort_by(|(_, a), (_, b)| b.cmp(a));
+        assert_eq!("a=3&C=2&b=1&b=0", params.to_string());
+
+        params.sort_by(|(a, _), (b, _)| a.to_lowercase().cmp(&b.to_lowercase()));
+        assert_eq!("a=3&b=1&b=0&C=2", params.to_string());
+    }
+
+    #[test]
+    fn merge() {
+        let base = UrlSearchParams::try_from("a=1&b=2&a=3").unwrap();
+        let other = UrlSearchParams::try_from("a=x&c=y&a=z").unwrap();
+
+        let mut params = base.clone();
+        params.merge(other.clone(), MergeStrategy::Overwrite);
+        assert_eq!("b=2&a=x&c=y&a=z", params.to_string());
+
+        let mut params = base.clone();
+        params.merge(other.clone(), MergeStrategy::KeepExisting);
+        assert_eq!("a=1&b=2&a=3&c=y", params.to_string());
+
+        let mut params = base.clone();
+        params.merge(other, MergeStrategy::AppendDuplicate);
+        assert_eq!("a=1&b=2&a=3&a=x&c=y&a=z", params.to_string());
+
+        let mut params = base.clone();
+        params.merge(UrlSearchParams::new(), MergeStrategy::Overwrite);
+        assert_eq!(base.to_string(), params.to_string());
+    }
+
+    #[test]
+    fn diff() {
+        let old = UrlSearchParams::try_from("a=1&b=2&b=3&c=%20").unwrap();
+        let new = UrlSearchParams::try_from("c=+&b=3&d=4&a=0").unwrap();
+
+        let query_diff = old.diff(&new);
+        assert_eq!(vec![("d".to_string(), "4".to_string())], query_diff.added);
+        assert!(query_diff.removed.is_empty());
+
+        let keys: Vec<&str> = query_diff.changed.iter().map(|changed| changed.key.as_str()).collect();
+        assert_eq!(vec!["a", "c"], keys);
+        assert_eq!(crate::diff("a=1&b=2&b=3&c=%20", "c=+&b=3&d=4&a=0"), query_diff);
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn contains_all_and_is_subset() {
+        let params = UrlSearchParams::try_from("q=rust&page=2&tag=a&tag=b").unwrap();
+
+        assert!(params.contains_all(&UrlSearchParams::try_from("tag=b&q=rust").unwrap()));
+        assert!(params.contains_all(&UrlSearchParams::try_from("tag=a&tag=a").unwrap()));
+        assert!(params.contains_all(&UrlSearchParams::new()));
+        assert!(params.contains_all(&params));
+        assert!(!params.contains_all(&UrlSearchParams::try_from("page=3").unwrap()));
+        assert!(!params.contains_all(&UrlSearchParams::try_from("q=rust&lang=en").unwrap()));
+
+        assert!(UrlSearchParams::try_from("page=%32").unwrap().is_subset(&params));
+        assert!(!params.is_subset(&UrlSearchParams::try_from("q=rust").unwrap()));
+        assert!(UrlSearchParams::new().is_subset(&UrlSearchParams::new()));
+    }
+
+    #[test]
+    fn capacity() {
+        let mut params = UrlSearchParams::with_capacity(8);
+        assert!(params.is_empty());
+        assert!(params.capacity() >= 8);
+
+        let capacity = params.capacity();
+        for index in 0..capacity {
+            params.append("key", &index.to_string());
+        }
+        assert_eq!(capacity, params.capacity());
+
+        params.reserve(10);
+        assert!(params.capacity() >= capacity + 10);
+        assert_eq!(capacity, params.len());
+    }
+
+    #[test]
+    fn toggle() {
+        let mut params = UrlSearchParams::try_from("size=m&color=red&size=l&color=red").unwrap();
+
+        assert!(!params.toggle("color", "red"));
+        assert_eq!("size=m&size=l", params.to_string());
+
+        assert!(params.toggle("color", "red"));
+        assert_eq!("size=m&size=l&color=red", params.to_string());
+
+        assert!(!params.toggle("size", "m"));
+        assert_eq!(vec!["l"], params.get_all("size"));
+
+        assert!(params.toggle("size", ""));
+        assert_eq!(vec!["l", ""], params.get_all("size"));
+    }
+
+    #[test]
+    fn defaults() {
+        let mut defaults: HashMap<String, String> = HashMap::new();
+        defaults.insert("per_page".to_string(), "20".to_string());
+        defaults.insert("page".to_string(), "1".to_string());
+        defaults.insert("sort".to_string(), "asc".to_string());
+
+        let params = UrlSearchParams::try_from("sort=desc&q=rust").unwrap();
+        assert_eq!("1", params.get_or("page", "1"));
+        assert_eq!("desc", params.get_or("sort", "asc"));
+
+        let params = params.with_defaults(defaults);
+        assert_eq!("sort=desc&q=rust&page=1&per_page=20", params.to_string());
+        assert_eq!("1", params.get_or("page", "unused"));
+
+        let params = UrlSearchParams::new().with_defaults(HashMap::new());
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn sub_params() {
+        let mut params = UrlSearchParams::new();
+        params.append("filter[price]", "10");
+        params.append("page", "2");
+        params.append("filter[color]", "red");
+        params.append("filter[]", "skipped");
+        params.append("filter[tags][]", "a");
+        params.append("filter[open", "skipped");
+        params.append("filterless", "x");
+
+        let filter = params.sub_params("filter[");
+        assert_eq!(vec![("price", "10"), ("color", "red"), ("tags[]", "a")], filter.entries().collect::<Vec<(&str, &str)>>());
+
+        let mut params = UrlSearchParams::new();
+        params.set_encoding(EncodeSet::FORM);
+        params.append("filter[q]", "a b");
+        assert_eq!("q=a+b", params.sub_params("filter[").to_string());
+
+        let mut params = UrlSearchParams::new();
+        params.append("user.name", "Bob");
+        params.append("user.", "skipped");
+        params.append("user.address.city", "Kyiv");
+
+        let user = params.sub_params("user.");
+        assert_eq!(vec![("name", "Bob"), ("address.city", "Kyiv")], user.entries().collect::<Vec<(&str, &str)>>());
+        assert_eq!(Some("Kyiv"), user.sub_params("address.").get("city"));
+
+        assert!(params.sub_params("missing.").is_empty());
+        assert_eq!(params, params.sub_params(""));
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use crate::{decode_uri_component, raw_segments_separated_by, try_decode_uri_component, UrlSearchParamsError, SYMBOL};
+
+/// Problem found by [parse_with_report], each variant holds the offending token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIssue {
+    /// Raw key or value with malformed percent-encoding, kept as is in the
+    /// parsed pair
+    MalformedEscape(String),
+    /// Raw key or value decoded into invalid UTF-8, replaced with U+FFFD
+    /// replacement character in the parsed pair
+    InvalidUtf8(String),
+    /// Raw pair with empty key, dropped
+    EmptyKey(String),
+    /// Decoded key appearing more than once, all of its pairs are kept
+    DuplicateKey(String),
+}
+
+/// Convert given string into key-value pairs, in the order they appear,
+/// collecting problems instead of failing on the first one
+///
+/// Pairs are decoded the same way [parse_url_search_params](crate::parse_url_search_params)
+/// does, but every pair is kept, including duplicate keys. Each problem
+/// found along the way is reported as a [ParseIssue].
+///
+/// # Examples
+///
+/// ```
+/// use url_search_params::{parse_with_report, ParseIssue};
+///
+/// let (pairs, issues) = parse_with_report("a=1&=orphan&b=100%");
+///
+/// assert_eq!(pairs, vec![
+///     ("a".to_string(), "1".to_string()),
+///     ("b".to_string(), "100%".to_string()),
+/// ]);
+/// assert_eq!(issues, vec![
+///     ParseIssue::EmptyKey("=orphan".to_string()),
+///     ParseIssue::MalformedEscape("100%".to_string()),
+/// ]);
+/// ```
+pub fn parse_with_report(params: &str) -> (Vec<(String, String)>, Vec<ParseIssue>) {
+    let mut pairs : Vec<(String, String)> = vec![];
+    let mut issues : Vec<ParseIssue> = vec![];
+    let mut keys : HashSet<String> = HashSet::new();
+
+    for param in raw_segments_separated_by(params, &['&']) {
+        let (key, value) = param.split_once('=').unwrap_or((param, SYMBOL.empty_string));
+        if key.is_empty() {
+            issues.push(ParseIssue::EmptyKey(param.to_string()));
+            continue;
+        }
+
+        let key = decode_reporting(key, &mut issues);
+        let value = decode_reporting(value, &mut issues);
+
+        if !keys.insert(key.to_string()) {
+            issues.push(ParseIssue::DuplicateKey(key.to_string()));
+        }
+        pairs.push((key, value));
+    }
+
+    (pairs, issues)
+}
+
+fn decode_reporting(component: &str, issues: &mut Vec<ParseIssue>) -> String {
+    match try_decode_uri_component(component) {
+        Ok(decoded) => return decoded,
+        Err(UrlSearchParamsError::InvalidUtf8(_)) => issues.push(ParseIssue::InvalidUtf8(component.to_string())),
+        Err(_) => issues.push(ParseIssue::MalformedEscape(component.to_string())),
+    }
+    decode_uri_component(component)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{parse_url_search_params, parse_with_report, ParseIssue};
+
+    #[test]
+    fn parse_with_report_mixed_input() {
+        let (pairs, issues) = parse_with_report("q=rust%20lang&=x&page=%ZZ&q=again&name=%FF&&ok=1");
+
+        assert_eq!(vec![
+            ("q".to_string(), "rust lang".to_string()),
+            ("page".to_string(), "%ZZ".to_string()),
+            ("q".to_string(), "again".to_string()),
+            ("name".to_string(), "\u{FFFD}".to_string()),
+            ("ok".to_string(), "1".to_string()),
+        ], pairs);
+
+        assert_eq!(vec![
+            ParseIssue::EmptyKey("=x".to_string()),
+            ParseIssue::MalformedEscape("%ZZ".to_string()),
+            ParseIssue::DuplicateKey("q".to_string()),
+            ParseIssue::InvalidUtf8("%FF".to_string()),
+        ], issues);
+    }
+
+    #[test]
+    fn parse_with_report_matches_parser() {
+        let search_params = "a=1&=x&&b==2&flag&a=%20&c";
+        let (pairs, _) = parse_with_report(search_params);
+
+        let pairs_map: HashMap<String, String> = pairs.into_iter().collect();
+        assert_eq!(parse_url_search_params(search_params), pairs_map);
+        assert_eq!(Some(&"=2".to_string()), pairs_map.get("b"));
+    }
+
+    #[test]
+    fn parse_with_report_valid_input() {
+        let (pairs, issues) = parse_with_report("a=1&b=2");
+        assert_eq!(2, pairs.len());
+        assert!(issues.is_empty());
+
+        let (pairs, issues) = parse_with_report("");
+        assert!(pairs.is_empty());
+        assert!(issues.is_empty());
+
+        let (pairs, issues) = parse_with_report(" \x0B ");
+        assert!(pairs.is_empty());
+        assert!(issues.is_empty());
+    }
+}